@@ -9,4 +9,25 @@ pub const STAKE_VAULT: &str = "stake_vault";
 #[constant]
 pub const REWARD_VAULT: &str = "reward_vault";
 
-pub const SECONDS_PER_YEAR: f64 = 365.0 * 24.0 * 60.0 * 60.0;
+#[constant]
+pub const PENDING_UPDATE: &str = "pending_update";
+
+#[constant]
+pub const RECEIPT_MINT: &str = "receipt_mint";
+
+/// Symbol written into the metadata of position receipts.
+pub const RECEIPT_SYMBOL: &str = "ZSTAKE";
+
+#[constant]
+pub const LIQUID_LOCKUP: &str = "zebec_liquid_lockup";
+
+pub const SECONDS_PER_YEAR: u64 = 365 * 24 * 60 * 60;
+
+/// Annual reward rates in `duration_map` are expressed in basis points.
+pub const REWARD_RATE_DENOMINATOR: u64 = 10_000;
+
+/// Unstake fees in `fee_info` are expressed in tenths of a percent.
+pub const FEE_DENOMINATOR: u64 = 1_000;
+
+/// Longest cooldown `set_lockup_options` accepts, 30 days.
+pub const MAX_COOLDOWN_SECONDS: i64 = 30 * 24 * 60 * 60;
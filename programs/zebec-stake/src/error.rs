@@ -31,5 +31,75 @@ pub enum ZbcnStakeError {
     #[msg("Stake Not Claimable")]
     StakeNotClaimable,
     #[msg("Minimum Stake Not Met")]
-    MinimumStakeNotMet
+    MinimumStakeNotMet,
+    #[msg("Math Overflow")]
+    MathOverflow,
+    #[msg("Stake Already Migrated")]
+    StakeAlreadyMigrated,
+    #[msg("Lock Period Not Offered")]
+    LockPeriodNotOffered,
+    #[msg("Lockup Not Migrated")]
+    LockupNotMigrated,
+    #[msg("Lockup Already Migrated")]
+    LockupAlreadyMigrated,
+    #[msg("Insufficient Reward Vault Balance")]
+    InsufficientRewardVault,
+    #[msg("Withdraw Exceeds Excess Rewards")]
+    WithdrawExceedsExcess,
+    #[msg("Invalid Role Holder")]
+    InvalidRoleHolder,
+    #[msg("Staking Paused")]
+    StakingPaused,
+    #[msg("Lockup Paused")]
+    LockupPaused,
+    #[msg("Update Timelocked")]
+    UpdateTimelocked,
+    #[msg("Update Not Ready")]
+    UpdateNotReady,
+    #[msg("Invalid Update Delay")]
+    InvalidUpdateDelay,
+    #[msg("Lock Not Extended")]
+    LockNotExtended,
+    #[msg("Position Mismatch")]
+    PositionMismatch,
+    #[msg("Position Not Claimed")]
+    PositionNotClaimed,
+    #[msg("Invalid Rent Payer")]
+    InvalidRentPayer,
+    #[msg("Invalid Penalty")]
+    InvalidPenalty,
+    #[msg("Invalid Penalty Destination")]
+    InvalidPenaltyDestination,
+    #[msg("Position Matured")]
+    PositionMatured,
+    #[msg("Compound Not Supported")]
+    CompoundNotSupported,
+    #[msg("Auto Renew Disabled")]
+    AutoRenewDisabled,
+    #[msg("Claim Disabled")]
+    ClaimDisabled,
+    #[msg("Cooldown Required")]
+    CooldownRequired,
+    #[msg("Unstake Already Requested")]
+    UnstakeAlreadyRequested,
+    #[msg("Unstake Not Requested")]
+    UnstakeNotRequested,
+    #[msg("Cooldown Not Elapsed")]
+    CooldownNotElapsed,
+    #[msg("Invalid Cooldown")]
+    InvalidCooldown,
+    #[msg("Transfers Disabled")]
+    TransfersDisabled,
+    #[msg("Receipt Required")]
+    ReceiptRequired,
+    #[msg("Position Tokenized")]
+    PositionTokenized,
+    #[msg("Liquid Lockup Empty")]
+    LiquidLockupEmpty,
+    #[msg("Invalid Delegate")]
+    InvalidDelegate,
+    #[msg("Invalid Fee")]
+    InvalidFee,
+    #[msg("Legacy Migration Pending")]
+    LegacyMigrationPending,
 }
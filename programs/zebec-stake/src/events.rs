@@ -1,5 +1,7 @@
 use anchor_lang::prelude::*;
-use crate::state::DurationMap;
+use crate::state::{
+    DurationMap, LockupOptions, LockupRole, LockupState, PenaltyDestination, PenaltySchedule,
+};
 
 #[event]
 pub struct LockupInitialized {
@@ -43,4 +45,320 @@ pub struct LockupUpdated {
     pub fee_vault: Pubkey,
     pub minimum_stake: u64,
     pub duration_map: Vec<DurationMap>,
-}
\ No newline at end of file
+}
+
+#[event]
+pub struct StakeMigrated {
+    pub staker: Pubkey,
+    pub lockup: Pubkey,
+    pub nonce: u64,
+    pub lock_period: i64,
+    pub reward_rate: u64,
+    pub fee: u64,
+    pub reward_amount: u64,
+}
+
+#[event]
+pub struct LockupMigrated {
+    pub lockup: Pubkey,
+    pub total_staked: u64,
+}
+
+#[event]
+pub struct RewardsFunded {
+    pub lockup: Pubkey,
+    pub funder: Pubkey,
+    pub amount: u64,
+    pub reward_vault_balance: u64,
+    pub reward_liability: u64,
+}
+
+#[event]
+pub struct ExcessRewardsWithdrawn {
+    pub lockup: Pubkey,
+    pub authority: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub reward_vault_balance: u64,
+    pub reward_liability: u64,
+}
+
+#[event]
+pub struct AuthorityProposed {
+    pub lockup: Pubkey,
+    pub authority: Pubkey,
+    pub pending_authority: Pubkey,
+}
+
+#[event]
+pub struct AuthorityTransferred {
+    pub lockup: Pubkey,
+    pub previous_authority: Pubkey,
+    pub new_authority: Pubkey,
+}
+
+#[event]
+pub struct RoleGranted {
+    pub lockup: Pubkey,
+    pub authority: Pubkey,
+    pub role: LockupRole,
+    pub previous_holder: Pubkey,
+    pub holder: Pubkey,
+}
+
+#[event]
+pub struct RoleRevoked {
+    pub lockup: Pubkey,
+    pub authority: Pubkey,
+    pub role: LockupRole,
+    pub previous_holder: Pubkey,
+}
+
+#[event]
+pub struct LockupStateChanged {
+    pub lockup: Pubkey,
+    pub pauser: Pubkey,
+    pub previous_state: LockupState,
+    pub state: LockupState,
+}
+
+#[event]
+pub struct LockupUpdateQueued {
+    pub lockup: Pubkey,
+    pub updater: Pubkey,
+    pub fee: u64,
+    pub fee_vault: Pubkey,
+    pub minimum_stake: u64,
+    pub duration_map: Vec<DurationMap>,
+    pub update_delay: Option<i64>,
+    pub effective_time: i64,
+}
+
+#[event]
+pub struct LockupUpdateExecuted {
+    pub lockup: Pubkey,
+    pub executor: Pubkey,
+    pub update_delay: i64,
+}
+
+#[event]
+pub struct LockupUpdateCancelled {
+    pub lockup: Pubkey,
+    pub canceller: Pubkey,
+}
+
+#[event]
+pub struct PartiallyUnstaked {
+    pub staker: Pubkey,
+    pub lockup: Pubkey,
+    pub nonce: u64,
+    pub unstake_amount: u64,
+    pub reward_amount: u64,
+    pub fee_amount: u64,
+    pub remaining_amount: u64,
+    pub remaining_reward_amount: u64,
+    pub lock_period: i64,
+}
+
+#[event]
+pub struct LockupOptionsUpdated {
+    pub lockup: Pubkey,
+    pub updater: Pubkey,
+    pub options: LockupOptions,
+}
+
+#[event]
+pub struct StakeIncreased {
+    pub staker: Pubkey,
+    pub lockup: Pubkey,
+    pub nonce: u64,
+    pub amount: u64,
+    pub staked_amount: u64,
+    pub created_time: i64,
+    pub reward_amount: u64,
+}
+
+#[event]
+pub struct LockExtended {
+    pub staker: Pubkey,
+    pub lockup: Pubkey,
+    pub nonce: u64,
+    pub previous_lock_period: i64,
+    pub lock_period: i64,
+    pub reward_rate: u64,
+    pub reward_amount: u64,
+}
+
+#[event]
+pub struct PositionsMerged {
+    pub staker: Pubkey,
+    pub lockup: Pubkey,
+    pub nonce: u64,
+    pub merged_nonces: Vec<u64>,
+    pub staked_amount: u64,
+    pub created_time: i64,
+    pub reward_rate: u64,
+    pub reward_amount: u64,
+}
+
+#[event]
+pub struct PositionClosed {
+    pub staker: Pubkey,
+    pub lockup: Pubkey,
+    pub nonce: u64,
+    pub rent_payer: Pubkey,
+}
+
+#[event]
+pub struct PenaltyScheduleUpdated {
+    pub lockup: Pubkey,
+    pub updater: Pubkey,
+    pub penalty_schedule: PenaltySchedule,
+}
+
+#[event]
+pub struct EarlyUnstaked {
+    pub staker: Pubkey,
+    pub lockup: Pubkey,
+    pub nonce: u64,
+    pub unstake_amount: u64,
+    pub penalty_amount: u64,
+    pub penalty_destination: PenaltyDestination,
+    pub forfeited_reward: u64,
+}
+
+#[event]
+pub struct AutoRenewUpdated {
+    pub staker: Pubkey,
+    pub lockup: Pubkey,
+    pub nonce: u64,
+    pub auto_renew: bool,
+    pub compound_on_renew: bool,
+}
+
+#[event]
+pub struct PositionRenewed {
+    pub staker: Pubkey,
+    pub lockup: Pubkey,
+    pub nonce: u64,
+    pub cranker: Pubkey,
+    pub previous_reward: u64,
+    pub compounded: bool,
+    pub staked_amount: u64,
+    pub created_time: i64,
+    pub reward_amount: u64,
+}
+
+#[event]
+pub struct Compounded {
+    pub staker: Pubkey,
+    pub lockup: Pubkey,
+    pub nonce: u64,
+    pub new_nonce: u64,
+    pub compounded_reward: u64,
+    pub staked_amount: u64,
+    pub lock_period: i64,
+    pub reward_amount: u64,
+}
+
+#[event]
+pub struct RewardsClaimed {
+    pub staker: Pubkey,
+    pub lockup: Pubkey,
+    pub nonce: u64,
+    pub amount: u64,
+    pub reward_claimed_so_far: u64,
+}
+
+#[event]
+pub struct UnstakeRequested {
+    pub staker: Pubkey,
+    pub lockup: Pubkey,
+    pub nonce: u64,
+    pub reward_amount: u64,
+    pub cooldown_end_time: i64,
+}
+
+#[event]
+pub struct Withdrawn {
+    pub staker: Pubkey,
+    pub lockup: Pubkey,
+    pub nonce: u64,
+    pub unstake_amount: u64,
+    pub fee_amount: u64,
+}
+
+#[event]
+pub struct PositionTransferred {
+    pub lockup: Pubkey,
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub nonce: u64,
+    pub new_nonce: u64,
+    pub staked_amount: u64,
+}
+
+#[event]
+pub struct ReceiptMinted {
+    pub staker: Pubkey,
+    pub lockup: Pubkey,
+    pub nonce: u64,
+    pub receipt_mint: Pubkey,
+}
+
+#[event]
+pub struct ReceiptBurned {
+    pub holder: Pubkey,
+    pub lockup: Pubkey,
+    pub nonce: u64,
+    pub receipt_mint: Pubkey,
+}
+
+#[event]
+pub struct LiquidLockupInitialized {
+    pub name: String,
+    pub creator: Pubkey,
+    pub liquid_lockup: Pubkey,
+    pub receipt_mint: Pubkey,
+}
+
+#[event]
+pub struct LiquidStaked {
+    pub staker: Pubkey,
+    pub liquid_lockup: Pubkey,
+    pub amount: u64,
+    pub receipt_amount: u64,
+}
+
+#[event]
+pub struct LiquidUnstaked {
+    pub staker: Pubkey,
+    pub liquid_lockup: Pubkey,
+    pub receipt_amount: u64,
+    pub principal_amount: u64,
+    pub reward_amount: u64,
+}
+
+#[event]
+pub struct LiquidRewardsFunded {
+    pub liquid_lockup: Pubkey,
+    pub funder: Pubkey,
+    pub amount: u64,
+    pub total_staked: u64,
+    pub total_rewards: u64,
+    pub receipt_supply: u64,
+}
+
+#[event]
+pub struct DelegateUpdated {
+    pub staker: Pubkey,
+    pub lockup: Pubkey,
+    pub nonce: u64,
+    pub delegate: Option<Pubkey>,
+}
+
+#[event]
+pub struct LegacyMigrationCompleted {
+    pub lockup: Pubkey,
+    pub operator: Pubkey,
+}
@@ -0,0 +1,38 @@
+use crate::{error::ZbcnStakeError, events::AuthorityTransferred, Lockup, LOCKUP};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    pub new_authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [LOCKUP.as_bytes(), lockup.stake_info.name.as_bytes()],
+        bump,
+        constraint = lockup.to_account_info().data_len() == 8 + Lockup::INIT_SPACE
+            @ ZbcnStakeError::LockupNotMigrated
+    )]
+    pub lockup: Box<Account<'info, Lockup>>,
+}
+
+pub fn handler(ctx: Context<AcceptAuthority>) -> Result<()> {
+    let lockup = &mut ctx.accounts.lockup;
+    let new_authority = &ctx.accounts.new_authority;
+
+    require!(
+        lockup.pending_authority == Some(new_authority.key()),
+        ZbcnStakeError::UnAuthorized
+    );
+
+    let previous_authority = lockup.stake_info.creator;
+    lockup.stake_info.creator = new_authority.key();
+    lockup.roles.reassign(previous_authority, new_authority.key());
+    lockup.pending_authority = None;
+
+    emit!(AuthorityTransferred {
+        lockup: lockup.key(),
+        previous_authority,
+        new_authority: new_authority.key(),
+    });
+
+    Ok(())
+}
@@ -0,0 +1,46 @@
+use crate::{
+    constants::{LOCKUP, PENDING_UPDATE},
+    error::ZbcnStakeError,
+    events::LockupUpdateCancelled,
+    state::{LockupRole, PendingLockupUpdate},
+    Lockup,
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct CancelLockupUpdate<'info> {
+    pub canceller: Signer<'info>,
+    #[account(
+        seeds = [LOCKUP.as_bytes(), lockup.stake_info.name.as_bytes()],
+        bump,
+        constraint = lockup.to_account_info().data_len() == 8 + Lockup::INIT_SPACE
+            @ ZbcnStakeError::LockupNotMigrated
+    )]
+    pub lockup: Box<Account<'info, Lockup>>,
+    #[account(
+        mut,
+        close = payer,
+        has_one = lockup,
+        has_one = payer,
+        seeds = [PENDING_UPDATE.as_bytes(), lockup.key().as_ref()],
+        bump
+    )]
+    pub pending_update: Box<Account<'info, PendingLockupUpdate>>,
+    #[account(mut)]
+    /// CHECK: rent refund destination, matched against `pending_update.payer`
+    pub payer: AccountInfo<'info>,
+}
+
+pub fn handler(ctx: Context<CancelLockupUpdate>) -> Result<()> {
+    let lockup = &ctx.accounts.lockup;
+    let canceller = &ctx.accounts.canceller;
+
+    lockup.require_role(LockupRole::ConfigAdmin, canceller.key())?;
+
+    emit!(LockupUpdateCancelled {
+        lockup: lockup.key(),
+        canceller: canceller.key(),
+    });
+
+    Ok(())
+}
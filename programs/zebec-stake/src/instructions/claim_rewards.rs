@@ -0,0 +1,113 @@
+use crate::{
+    constants::REWARD_VAULT, error::ZbcnStakeError, events::RewardsClaimed, state::LockupState,
+    utils::transfer_from_vault, Lockup, UserStakeData, LOCKUP,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount},
+};
+
+#[derive(Accounts)]
+#[instruction(nonce: u64)]
+pub struct ClaimRewards<'info> {
+    #[account(mut)]
+    pub staker: Signer<'info>,
+    #[account(mut)]
+    pub fee_payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [LOCKUP.as_bytes(), lockup.stake_info.name.as_bytes()],
+        bump,
+        constraint = lockup.to_account_info().data_len() == 8 + Lockup::INIT_SPACE
+            @ ZbcnStakeError::LockupNotMigrated
+    )]
+    pub lockup: Box<Account<'info, Lockup>>,
+    #[account(
+        mut,
+        seeds = [staker.key().as_ref(), lockup.key().as_ref(), &nonce.to_le_bytes()],
+        bump
+    )]
+    pub stake_pda: Box<Account<'info, UserStakeData>>,
+    #[account(
+        constraint = reward_token.key() == lockup.reward_token.token_address
+            @ ZbcnStakeError::InvalidRewardToken
+    )]
+    pub reward_token: Box<Account<'info, Mint>>,
+    #[account(
+        init_if_needed,
+        payer = fee_payer,
+        associated_token::mint = reward_token,
+        associated_token::authority = staker,
+    )]
+    pub staker_reward_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        seeds = [REWARD_VAULT.as_bytes(), lockup.key().as_ref()],
+        bump
+    )]
+    /// CHECK: seeds has been checked
+    pub reward_vault: AccountInfo<'info>,
+    #[account(
+        mut,
+        associated_token::mint = reward_token,
+        associated_token::authority = reward_vault,
+    )]
+    pub reward_vault_token_account: Box<Account<'info, TokenAccount>>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<ClaimRewards>, nonce: u64) -> Result<()> {
+    let lockup = &mut ctx.accounts.lockup;
+    let stake_pda = &mut ctx.accounts.stake_pda;
+    let current_time = Clock::get()?.unix_timestamp;
+
+    require!(lockup.options.claim_enabled, ZbcnStakeError::ClaimDisabled);
+    require!(
+        !stake_pda.stake_claimed,
+        ZbcnStakeError::StakeAlreadyClaimed
+    );
+    require!(
+        !stake_pda.is_tokenized(),
+        ZbcnStakeError::PositionTokenized
+    );
+    // Emergency withdrawal forfeits reward, so claims stop with it.
+    require!(
+        lockup.state == LockupState::Active || lockup.state == LockupState::StakingPaused,
+        ZbcnStakeError::LockupPaused
+    );
+
+    let amount = stake_pda.claimable_reward_at(current_time)?;
+    require!(amount > 0, ZbcnStakeError::RewardIsZero);
+
+    let lockup_key = lockup.key();
+    transfer_from_vault(
+        ctx.accounts.token_program.to_account_info(),
+        ctx.accounts.reward_vault_token_account.to_account_info(),
+        ctx.accounts.staker_reward_token_account.to_account_info(),
+        ctx.accounts.reward_vault.to_account_info(),
+        &[
+            REWARD_VAULT.as_bytes(),
+            lockup_key.as_ref(),
+            &[ctx.bumps.reward_vault],
+        ],
+        amount,
+    )?;
+
+    stake_pda.reward_claimed_so_far = stake_pda
+        .reward_claimed_so_far
+        .checked_add(amount)
+        .ok_or(ZbcnStakeError::MathOverflow)?;
+    lockup.record_reward_claim(amount)?;
+
+    emit!(RewardsClaimed {
+        staker: stake_pda.staker,
+        lockup: lockup_key,
+        nonce,
+        amount,
+        reward_claimed_so_far: stake_pda.reward_claimed_so_far,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,38 @@
+use crate::{error::ZbcnStakeError, events::PositionClosed, Lockup, UserStakeData, LOCKUP};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+#[instruction(nonce: u64)]
+pub struct ClosePosition<'info> {
+    pub staker: Signer<'info>,
+    #[account(
+        seeds = [LOCKUP.as_bytes(), lockup.stake_info.name.as_bytes()],
+        bump,
+        constraint = lockup.to_account_info().data_len() == 8 + Lockup::INIT_SPACE
+            @ ZbcnStakeError::LockupNotMigrated
+    )]
+    pub lockup: Box<Account<'info, Lockup>>,
+    #[account(
+        mut,
+        close = rent_payer,
+        has_one = rent_payer @ ZbcnStakeError::InvalidRentPayer,
+        constraint = stake_pda.stake_claimed @ ZbcnStakeError::PositionNotClaimed,
+        seeds = [staker.key().as_ref(), lockup.key().as_ref(), &nonce.to_le_bytes()],
+        bump
+    )]
+    pub stake_pda: Box<Account<'info, UserStakeData>>,
+    #[account(mut)]
+    /// CHECK: matched against `stake_pda.rent_payer`
+    pub rent_payer: AccountInfo<'info>,
+}
+
+pub fn handler(ctx: Context<ClosePosition>, nonce: u64) -> Result<()> {
+    emit!(PositionClosed {
+        staker: ctx.accounts.staker.key(),
+        lockup: ctx.accounts.lockup.key(),
+        nonce,
+        rent_payer: ctx.accounts.rent_payer.key(),
+    });
+
+    Ok(())
+}
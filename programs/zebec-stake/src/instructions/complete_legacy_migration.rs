@@ -0,0 +1,42 @@
+use crate::{
+    error::ZbcnStakeError,
+    events::LegacyMigrationCompleted,
+    state::{Lockup, LockupRole},
+    LOCKUP,
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct CompleteLegacyMigration<'info> {
+    pub operator: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [LOCKUP.as_bytes(), lockup.stake_info.name.as_bytes()],
+        bump,
+        constraint = lockup.to_account_info().data_len() == 8 + Lockup::INIT_SPACE
+            @ ZbcnStakeError::LockupNotMigrated
+    )]
+    pub lockup: Box<Account<'info, Lockup>>,
+}
+
+pub fn handler(ctx: Context<CompleteLegacyMigration>) -> Result<()> {
+    let lockup = &mut ctx.accounts.lockup;
+    let operator = &ctx.accounts.operator;
+
+    lockup.require_role(LockupRole::Operator, operator.key())?;
+    require!(
+        lockup.legacy_migration_pending,
+        ZbcnStakeError::LockupAlreadyMigrated
+    );
+
+    // Every legacy position must have gone through `migrate_stake` by now, so
+    // `total_reward_liability` covers all reward owed from the vault.
+    lockup.legacy_migration_pending = false;
+
+    emit!(LegacyMigrationCompleted {
+        lockup: lockup.key(),
+        operator: operator.key(),
+    });
+
+    Ok(())
+}
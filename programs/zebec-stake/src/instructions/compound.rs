@@ -0,0 +1,195 @@
+use crate::{
+    constants::REWARD_VAULT,
+    error::ZbcnStakeError,
+    events::Compounded,
+    state::UserNonce,
+    utils::{calculate_reward, transfer_from_vault, LockPeriod},
+    Lockup, UserStakeData, LOCKUP, STAKE_VAULT,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+
+#[derive(Accounts)]
+#[instruction(params: CompoundParams)]
+pub struct Compound<'info> {
+    pub staker: Signer<'info>,
+    #[account(mut)]
+    pub fee_payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [LOCKUP.as_bytes(), lockup.stake_info.name.as_bytes()],
+        bump,
+        constraint = lockup.to_account_info().data_len() == 8 + Lockup::INIT_SPACE
+            @ ZbcnStakeError::LockupNotMigrated
+    )]
+    pub lockup: Box<Account<'info, Lockup>>,
+    #[account(
+        mut,
+        seeds = [staker.key().as_ref(), lockup.key().as_ref(), &params.nonce.to_le_bytes()],
+        bump
+    )]
+    pub stake_pda: Box<Account<'info, UserStakeData>>,
+    #[account(
+        mut,
+        seeds = [staker.key().as_ref(), lockup.key().as_ref()],
+        bump
+    )]
+    pub user_nonce: Box<Account<'info, UserNonce>>,
+    #[account(
+        init,
+        payer = fee_payer,
+        space = 8 + UserStakeData::INIT_SPACE,
+        seeds = [staker.key().as_ref(), lockup.key().as_ref(), &params.new_nonce.to_le_bytes()],
+        bump
+    )]
+    pub new_stake_pda: Box<Account<'info, UserStakeData>>,
+    #[account(
+        seeds = [STAKE_VAULT.as_bytes(), lockup.key().as_ref()],
+        bump
+    )]
+    /// CHECK: seeds has been checked
+    pub stake_vault: AccountInfo<'info>,
+    #[account(
+        mut,
+        associated_token::mint = lockup.staked_token.token_address,
+        associated_token::authority = stake_vault,
+    )]
+    pub stake_vault_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        seeds = [REWARD_VAULT.as_bytes(), lockup.key().as_ref()],
+        bump
+    )]
+    /// CHECK: seeds has been checked
+    pub reward_vault: AccountInfo<'info>,
+    #[account(
+        mut,
+        associated_token::mint = lockup.reward_token.token_address,
+        associated_token::authority = reward_vault,
+    )]
+    pub reward_vault_token_account: Box<Account<'info, TokenAccount>>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct CompoundParams {
+    /// Matured position to compound.
+    pub nonce: u64,
+    /// Nonce of the position opened with principal plus reward.
+    pub new_nonce: u64,
+    /// Lock period in seconds for the new position.
+    pub lock_period: i64,
+}
+
+/// Claims a matured position and restakes its principal plus reward as a new
+/// position. The principal never leaves the stake vault, so no unstake fee is
+/// charged here; the new position pays its own fee when it is unstaked.
+pub fn handler(ctx: Context<Compound>, params: CompoundParams) -> Result<()> {
+    let lockup = &mut ctx.accounts.lockup;
+    let stake_pda = &mut ctx.accounts.stake_pda;
+    let new_stake_pda = &mut ctx.accounts.new_stake_pda;
+    let user_nonce = &mut ctx.accounts.user_nonce;
+    let current_time = Clock::get()?.unix_timestamp;
+
+    require!(
+        user_nonce.nonce == params.new_nonce,
+        ZbcnStakeError::InvaildNonce
+    );
+
+    let compounded_reward = stake_pda.unclaimed_reward();
+    let staked_amount = stake_pda
+        .staked_amount
+        .checked_add(compounded_reward)
+        .ok_or(ZbcnStakeError::MathOverflow)?;
+    let lock_period = LockPeriod::from_seconds(params.lock_period)?;
+    let reward_rate = run_validations(lockup, stake_pda, staked_amount, lock_period, current_time)?;
+    let reward_amount = calculate_reward(staked_amount, reward_rate, lock_period)?;
+
+    if compounded_reward > 0 {
+        let lockup_key = lockup.key();
+        transfer_from_vault(
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.reward_vault_token_account.to_account_info(),
+            ctx.accounts.stake_vault_token_account.to_account_info(),
+            ctx.accounts.reward_vault.to_account_info(),
+            &[
+                REWARD_VAULT.as_bytes(),
+                lockup_key.as_ref(),
+                &[ctx.bumps.reward_vault],
+            ],
+            compounded_reward,
+        )?;
+    }
+
+    stake_pda.stake_claimed = true;
+    lockup.record_unstake(stake_pda.staked_amount, compounded_reward, 0)?;
+
+    new_stake_pda.staked_amount = staked_amount;
+    new_stake_pda.created_time = current_time;
+    new_stake_pda.nonce = params.new_nonce;
+    new_stake_pda.lock_period = lock_period.as_seconds();
+    new_stake_pda.staker = stake_pda.staker;
+    new_stake_pda.lockup = lockup.key();
+    new_stake_pda.reward_rate = reward_rate;
+    new_stake_pda.fee = lockup.fee_info.fee;
+    new_stake_pda.penalty = lockup.penalty_for(lock_period);
+    new_stake_pda.cooldown_seconds = lockup.options.cooldown_seconds;
+    new_stake_pda.reward_amount = reward_amount;
+    new_stake_pda.rent_payer = ctx.accounts.fee_payer.key();
+    user_nonce.nonce += 1;
+    lockup.record_stake(staked_amount, reward_amount)?;
+
+    let reward_vault_token_account = &mut ctx.accounts.reward_vault_token_account;
+    reward_vault_token_account.reload()?;
+    lockup.ensure_reward_coverage(reward_vault_token_account.amount)?;
+
+    emit!(Compounded {
+        staker: stake_pda.staker,
+        lockup: lockup.key(),
+        nonce: params.nonce,
+        new_nonce: params.new_nonce,
+        compounded_reward,
+        staked_amount,
+        lock_period: new_stake_pda.lock_period,
+        reward_amount,
+    });
+
+    Ok(())
+}
+
+/// Returns the annual reward rate offered for the new position's lock period.
+fn run_validations(
+    lockup: &Lockup,
+    stake_pda: &UserStakeData,
+    staked_amount: u64,
+    lock_period: LockPeriod,
+    current_time: i64,
+) -> Result<u64> {
+    if stake_pda.stake_claimed {
+        return Err(ZbcnStakeError::RewardAlreadyClaimed.into());
+    }
+    require!(
+        !stake_pda.is_unbonding(),
+        ZbcnStakeError::UnstakeAlreadyRequested
+    );
+    require!(
+        !stake_pda.is_tokenized(),
+        ZbcnStakeError::PositionTokenized
+    );
+
+    require!(
+        lockup.reward_token.token_address == lockup.staked_token.token_address,
+        ZbcnStakeError::CompoundNotSupported
+    );
+    lockup.require_staking_open()?;
+    require!(
+        stake_pda.is_ended_at(current_time)?,
+        ZbcnStakeError::StakeRewardNotClaimable
+    );
+    require!(
+        staked_amount >= lockup.stake_info.minimum_stake,
+        ZbcnStakeError::MinimumStakeNotMet
+    );
+
+    lockup.offered_reward_rate(lock_period)
+}
@@ -0,0 +1,157 @@
+use crate::{
+    constants::REWARD_VAULT,
+    error::ZbcnStakeError,
+    events::PositionRenewed,
+    utils::{calculate_reward, transfer_from_vault},
+    Lockup, UserStakeData, LOCKUP, STAKE_VAULT,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+
+#[derive(Accounts)]
+#[instruction(nonce: u64)]
+pub struct CrankRenew<'info> {
+    pub cranker: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [LOCKUP.as_bytes(), lockup.stake_info.name.as_bytes()],
+        bump,
+        constraint = lockup.to_account_info().data_len() == 8 + Lockup::INIT_SPACE
+            @ ZbcnStakeError::LockupNotMigrated
+    )]
+    pub lockup: Box<Account<'info, Lockup>>,
+    /// CHECK: only used to derive the stake pda
+    pub staker: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [staker.key().as_ref(), lockup.key().as_ref(), &nonce.to_le_bytes()],
+        bump
+    )]
+    pub stake_pda: Box<Account<'info, UserStakeData>>,
+    #[account(
+        seeds = [STAKE_VAULT.as_bytes(), lockup.key().as_ref()],
+        bump
+    )]
+    /// CHECK: seeds has been checked
+    pub stake_vault: AccountInfo<'info>,
+    #[account(
+        mut,
+        associated_token::mint = lockup.staked_token.token_address,
+        associated_token::authority = stake_vault,
+    )]
+    pub stake_vault_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        seeds = [REWARD_VAULT.as_bytes(), lockup.key().as_ref()],
+        bump
+    )]
+    /// CHECK: seeds has been checked
+    pub reward_vault: AccountInfo<'info>,
+    #[account(
+        mut,
+        associated_token::mint = lockup.reward_token.token_address,
+        associated_token::authority = reward_vault,
+    )]
+    pub reward_vault_token_account: Box<Account<'info, TokenAccount>>,
+    /// Receives the matured reward; required unless the position compounds.
+    #[account(
+        mut,
+        token::mint = lockup.reward_token.token_address,
+        token::authority = staker,
+    )]
+    pub staker_reward_token_account: Option<Box<Account<'info, TokenAccount>>>,
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handler(ctx: Context<CrankRenew>, nonce: u64) -> Result<()> {
+    let lockup = &mut ctx.accounts.lockup;
+    let stake_pda = &mut ctx.accounts.stake_pda;
+    let current_time = Clock::get()?.unix_timestamp;
+
+    require!(
+        !stake_pda.stake_claimed,
+        ZbcnStakeError::StakeAlreadyClaimed
+    );
+    require!(
+        !stake_pda.is_unbonding(),
+        ZbcnStakeError::UnstakeAlreadyRequested
+    );
+    require!(stake_pda.auto_renew, ZbcnStakeError::AutoRenewDisabled);
+    lockup.require_staking_open()?;
+    require!(
+        stake_pda.is_ended_at(current_time)?,
+        ZbcnStakeError::StakeRewardNotClaimable
+    );
+
+    let lock_period = stake_pda.lock_period()?;
+    let reward_rate = lockup.offered_reward_rate(lock_period)?;
+    let previous_reward = stake_pda.unclaimed_reward();
+    let compounded = stake_pda.compound_on_renew;
+
+    if previous_reward > 0 {
+        let destination = if compounded {
+            ctx.accounts.stake_vault_token_account.to_account_info()
+        } else {
+            ctx.accounts
+                .staker_reward_token_account
+                .as_ref()
+                .ok_or(ErrorCode::AccountNotEnoughKeys)?
+                .to_account_info()
+        };
+        let lockup_key = lockup.key();
+        transfer_from_vault(
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.reward_vault_token_account.to_account_info(),
+            destination,
+            ctx.accounts.reward_vault.to_account_info(),
+            &[
+                REWARD_VAULT.as_bytes(),
+                lockup_key.as_ref(),
+                &[ctx.bumps.reward_vault],
+            ],
+            previous_reward,
+        )?;
+    }
+
+    let staked_amount = if compounded {
+        stake_pda
+            .staked_amount
+            .checked_add(previous_reward)
+            .ok_or(ZbcnStakeError::MathOverflow)?
+    } else {
+        stake_pda.staked_amount
+    };
+
+    // The new lock starts where the previous one ended, so renewal does not
+    // depend on when the crank runs.
+    let created_time = lock_period.end_time(stake_pda.created_time)?;
+    let reward_amount = calculate_reward(staked_amount, reward_rate, lock_period)?;
+
+    stake_pda.staked_amount = staked_amount;
+    stake_pda.created_time = created_time;
+    stake_pda.reward_rate = reward_rate;
+    stake_pda.fee = lockup.fee_info.fee;
+    stake_pda.penalty = lockup.penalty_for(lock_period);
+    stake_pda.reward_amount = reward_amount;
+    stake_pda.reward_claimed_so_far = 0;
+    stake_pda.vested_reward = 0;
+
+    lockup.record_renewal(previous_reward, compounded, reward_amount)?;
+
+    let reward_vault_token_account = &mut ctx.accounts.reward_vault_token_account;
+    reward_vault_token_account.reload()?;
+    lockup.ensure_reward_coverage(reward_vault_token_account.amount)?;
+
+    emit!(PositionRenewed {
+        staker: stake_pda.staker,
+        lockup: lockup.key(),
+        nonce,
+        cranker: ctx.accounts.cranker.key(),
+        previous_reward,
+        compounded,
+        staked_amount,
+        created_time,
+        reward_amount,
+    });
+
+    Ok(())
+}
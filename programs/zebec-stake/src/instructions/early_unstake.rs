@@ -0,0 +1,171 @@
+use crate::{
+    constants::REWARD_VAULT,
+    error::ZbcnStakeError,
+    events::EarlyUnstaked,
+    state::{LockupState, PenaltyDestination},
+    utils::{calculate_fee, transfer_from_vault},
+    Lockup, UserStakeData, LOCKUP, STAKE_VAULT,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount},
+};
+
+#[derive(Accounts)]
+#[instruction(nonce: u64)]
+pub struct EarlyUnstake<'info> {
+    #[account(mut)]
+    pub staker: Signer<'info>,
+    #[account(mut)]
+    pub fee_payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [LOCKUP.as_bytes(), lockup.stake_info.name.as_bytes()],
+        bump,
+        constraint = lockup.to_account_info().data_len() == 8 + Lockup::INIT_SPACE
+            @ ZbcnStakeError::LockupNotMigrated
+    )]
+    pub lockup: Box<Account<'info, Lockup>>,
+    #[account(
+        mut,
+        seeds = [staker.key().as_ref(), lockup.key().as_ref(), &nonce.to_le_bytes()],
+        bump
+    )]
+    pub stake_pda: Box<Account<'info, UserStakeData>>,
+    #[account(
+        constraint = stake_token.key() == lockup.staked_token.token_address
+            @ ZbcnStakeError::InvalidStakeToken
+    )]
+    pub stake_token: Box<Account<'info, Mint>>,
+    #[account(
+        init_if_needed,
+        payer = fee_payer,
+        associated_token::mint = stake_token,
+        associated_token::authority = staker,
+    )]
+    pub staker_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        seeds = [STAKE_VAULT.as_bytes(), lockup.key().as_ref()],
+        bump
+    )]
+    /// CHECK: seeds has been checked
+    pub stake_vault: AccountInfo<'info>,
+    #[account(
+        mut,
+        associated_token::mint = stake_token,
+        associated_token::authority = stake_vault,
+    )]
+    pub stake_vault_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        seeds = [REWARD_VAULT.as_bytes(), lockup.key().as_ref()],
+        bump
+    )]
+    /// CHECK: seeds has been checked
+    pub reward_vault: AccountInfo<'info>,
+    #[account(
+        mut,
+        associated_token::mint = lockup.reward_token.token_address,
+        associated_token::authority = reward_vault,
+    )]
+    pub reward_vault_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = stake_token,
+        associated_token::authority = lockup.fee_info.fee_vault,
+    )]
+    pub fee_vault_token_account: Box<Account<'info, TokenAccount>>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<EarlyUnstake>, nonce: u64) -> Result<()> {
+    let lockup = &mut ctx.accounts.lockup;
+    let stake_pda = &mut ctx.accounts.stake_pda;
+    let current_time = Clock::get()?.unix_timestamp;
+
+    run_validations(lockup, stake_pda, current_time)?;
+
+    // Reward is forfeited and the penalty captured when the lock started is
+    // withheld from the principal in place of the unstake fee.
+    let penalty_amount = calculate_fee(stake_pda.staked_amount, stake_pda.penalty)?;
+    let unstake_amount = stake_pda
+        .staked_amount
+        .checked_sub(penalty_amount)
+        .ok_or(ZbcnStakeError::MathOverflow)?;
+    let forfeited_reward = stake_pda.unclaimed_reward();
+    let penalty_destination = lockup.penalty_schedule.destination;
+
+    let lockup_key = lockup.key();
+    let token_program = ctx.accounts.token_program.to_account_info();
+    let stake_vault_seeds: &[&[u8]] = &[
+        STAKE_VAULT.as_bytes(),
+        lockup_key.as_ref(),
+        &[ctx.bumps.stake_vault],
+    ];
+
+    transfer_from_vault(
+        token_program.clone(),
+        ctx.accounts.stake_vault_token_account.to_account_info(),
+        ctx.accounts.staker_token_account.to_account_info(),
+        ctx.accounts.stake_vault.to_account_info(),
+        stake_vault_seeds,
+        unstake_amount,
+    )?;
+    if penalty_amount > 0 {
+        let penalty_token_account = match penalty_destination {
+            PenaltyDestination::FeeVault => ctx.accounts.fee_vault_token_account.to_account_info(),
+            PenaltyDestination::RewardVault => {
+                ctx.accounts.reward_vault_token_account.to_account_info()
+            }
+        };
+        transfer_from_vault(
+            token_program,
+            ctx.accounts.stake_vault_token_account.to_account_info(),
+            penalty_token_account,
+            ctx.accounts.stake_vault.to_account_info(),
+            stake_vault_seeds,
+            penalty_amount,
+        )?;
+    }
+
+    stake_pda.stake_claimed = true;
+    stake_pda.reward_amount = stake_pda.reward_claimed_so_far;
+    lockup.record_early_unstake(stake_pda.staked_amount, forfeited_reward, penalty_amount)?;
+
+    emit!(EarlyUnstaked {
+        staker: stake_pda.staker,
+        lockup: lockup_key,
+        nonce,
+        unstake_amount,
+        penalty_amount,
+        penalty_destination,
+        forfeited_reward,
+    });
+
+    Ok(())
+}
+
+fn run_validations(lockup: &Lockup, stake_pda: &UserStakeData, current_time: i64) -> Result<()> {
+    if stake_pda.stake_claimed {
+        return Err(ZbcnStakeError::RewardAlreadyClaimed.into());
+    }
+    require!(
+        !stake_pda.is_tokenized(),
+        ZbcnStakeError::PositionTokenized
+    );
+
+    require!(
+        lockup.state != LockupState::Paused,
+        ZbcnStakeError::LockupPaused
+    );
+
+    // Matured positions leave through `unstake_zbcn` and keep their reward.
+    require!(
+        !stake_pda.is_ended_at(current_time)?,
+        ZbcnStakeError::PositionMatured
+    );
+
+    Ok(())
+}
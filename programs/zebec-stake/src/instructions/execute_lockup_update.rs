@@ -0,0 +1,64 @@
+use crate::{
+    constants::{LOCKUP, PENDING_UPDATE},
+    error::ZbcnStakeError,
+    events::{LockupUpdateExecuted, LockupUpdated},
+    state::PendingLockupUpdate,
+    Lockup,
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct ExecuteLockupUpdate<'info> {
+    pub executor: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [LOCKUP.as_bytes(), lockup.stake_info.name.as_bytes()],
+        bump,
+        constraint = lockup.to_account_info().data_len() == 8 + Lockup::INIT_SPACE
+            @ ZbcnStakeError::LockupNotMigrated
+    )]
+    pub lockup: Box<Account<'info, Lockup>>,
+    #[account(
+        mut,
+        close = payer,
+        has_one = lockup,
+        has_one = payer,
+        seeds = [PENDING_UPDATE.as_bytes(), lockup.key().as_ref()],
+        bump
+    )]
+    pub pending_update: Box<Account<'info, PendingLockupUpdate>>,
+    #[account(mut)]
+    /// CHECK: rent refund destination, matched against `pending_update.payer`
+    pub payer: AccountInfo<'info>,
+}
+
+pub fn handler(ctx: Context<ExecuteLockupUpdate>) -> Result<()> {
+    let lockup = &mut ctx.accounts.lockup;
+    let pending_update = &ctx.accounts.pending_update;
+
+    // Authorization was checked when the update was queued; once the delay has
+    // passed anyone may apply it.
+    require!(
+        Clock::get()?.unix_timestamp >= pending_update.effective_time,
+        ZbcnStakeError::UpdateNotReady
+    );
+
+    lockup.update_lockup(pending_update.params.clone())?;
+    if let Some(update_delay) = pending_update.update_delay {
+        lockup.update_delay = update_delay;
+    }
+
+    emit!(LockupUpdated {
+        fee: lockup.fee_info.fee,
+        fee_vault: lockup.fee_info.fee_vault,
+        minimum_stake: lockup.stake_info.minimum_stake,
+        duration_map: lockup.stake_info.duration_map.clone(),
+    });
+    emit!(LockupUpdateExecuted {
+        lockup: lockup.key(),
+        executor: ctx.accounts.executor.key(),
+        update_delay: lockup.update_delay,
+    });
+
+    Ok(())
+}
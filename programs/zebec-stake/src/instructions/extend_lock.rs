@@ -0,0 +1,120 @@
+use crate::{
+    error::ZbcnStakeError,
+    events::LockExtended,
+    utils::{calculate_reward, LockPeriod},
+    Lockup, UserStakeData, LOCKUP, REWARD_VAULT,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+
+#[derive(Accounts)]
+#[instruction(params: ExtendLockParams)]
+pub struct ExtendLock<'info> {
+    /// The position's staker or its delegate.
+    pub staker: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [LOCKUP.as_bytes(), lockup.stake_info.name.as_bytes()],
+        bump,
+        constraint = lockup.to_account_info().data_len() == 8 + Lockup::INIT_SPACE
+            @ ZbcnStakeError::LockupNotMigrated
+    )]
+    pub lockup: Box<Account<'info, Lockup>>,
+    #[account(
+        mut,
+        seeds = [stake_pda.staker.as_ref(), lockup.key().as_ref(), &params.nonce.to_le_bytes()],
+        bump,
+        constraint = stake_pda.can_manage(staker.key()) @ ZbcnStakeError::UnAuthorized
+    )]
+    pub stake_pda: Box<Account<'info, UserStakeData>>,
+    #[account(
+        seeds = [REWARD_VAULT.as_bytes(), lockup.key().as_ref()],
+        bump
+    )]
+    /// CHECK: seeds has been checked
+    pub reward_vault: AccountInfo<'info>,
+    #[account(
+        associated_token::mint = lockup.reward_token.token_address,
+        associated_token::authority = reward_vault,
+    )]
+    pub reward_vault_token_account: Box<Account<'info, TokenAccount>>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct ExtendLockParams {
+    pub nonce: u64,
+    /// New lock period in seconds; must be longer than the current one.
+    pub lock_period: i64,
+}
+
+pub fn handler(ctx: Context<ExtendLock>, params: ExtendLockParams) -> Result<()> {
+    let lockup = &mut ctx.accounts.lockup;
+    let stake_pda = &mut ctx.accounts.stake_pda;
+    let current_time = Clock::get()?.unix_timestamp;
+
+    let lock_period = LockPeriod::from_seconds(params.lock_period)?;
+    let reward_rate = run_validations(lockup, stake_pda, lock_period)?;
+
+    // Reward accrued and not yet claimed is carried forward as vested and the
+    // position restarts now under the longer lock, so it can only end later
+    // than before.
+    let accrued_reward = stake_pda.claimable_reward_at(current_time)?;
+    let reward_amount = accrued_reward
+        .checked_add(calculate_reward(
+            stake_pda.staked_amount,
+            reward_rate,
+            lock_period,
+        )?)
+        .ok_or(ZbcnStakeError::MathOverflow)?;
+
+    lockup.replace_reward_liability(stake_pda.unclaimed_reward(), reward_amount)?;
+    lockup.ensure_reward_coverage(ctx.accounts.reward_vault_token_account.amount)?;
+
+    let previous_lock_period = stake_pda.lock_period;
+    stake_pda.created_time = current_time;
+    stake_pda.lock_period = lock_period.as_seconds();
+    stake_pda.reward_rate = reward_rate;
+    stake_pda.penalty = lockup.penalty_for(lock_period);
+    stake_pda.reward_amount = reward_amount;
+    stake_pda.reward_claimed_so_far = 0;
+    stake_pda.vested_reward = accrued_reward;
+
+    emit!(LockExtended {
+        staker: stake_pda.staker,
+        lockup: lockup.key(),
+        nonce: params.nonce,
+        previous_lock_period,
+        lock_period: stake_pda.lock_period,
+        reward_rate,
+        reward_amount,
+    });
+
+    Ok(())
+}
+
+/// Returns the annual reward rate offered for the new lock period.
+fn run_validations(
+    lockup: &Lockup,
+    stake_pda: &UserStakeData,
+    lock_period: LockPeriod,
+) -> Result<u64> {
+    if stake_pda.stake_claimed {
+        return Err(ZbcnStakeError::StakeAlreadyClaimed.into());
+    }
+    require!(
+        !stake_pda.is_unbonding(),
+        ZbcnStakeError::UnstakeAlreadyRequested
+    );
+    require!(
+        !stake_pda.is_tokenized(),
+        ZbcnStakeError::PositionTokenized
+    );
+
+    lockup.require_staking_open()?;
+    require!(
+        lock_period > stake_pda.lock_period()?,
+        ZbcnStakeError::LockNotExtended
+    );
+
+    lockup.offered_reward_rate(lock_period)
+}
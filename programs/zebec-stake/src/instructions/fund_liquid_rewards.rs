@@ -0,0 +1,91 @@
+use crate::{
+    error::ZbcnStakeError, events::LiquidRewardsFunded, LiquidLockup, LIQUID_LOCKUP, REWARD_VAULT,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{transfer, Mint, Token, TokenAccount, Transfer},
+};
+
+#[derive(Accounts)]
+pub struct FundLiquidRewards<'info> {
+    #[account(mut)]
+    pub funder: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [LIQUID_LOCKUP.as_bytes(), liquid_lockup.name.as_bytes()],
+        bump
+    )]
+    pub liquid_lockup: Box<Account<'info, LiquidLockup>>,
+    #[account(
+        constraint = stake_token.key() == liquid_lockup.staked_token
+            @ ZbcnStakeError::InvalidStakeToken
+    )]
+    pub stake_token: Box<Account<'info, Mint>>,
+    #[account(
+        mut,
+        token::mint = stake_token,
+        token::authority = funder,
+    )]
+    pub funder_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        seeds = [REWARD_VAULT.as_bytes(), liquid_lockup.key().as_ref()],
+        bump
+    )]
+    /// CHECK: seeds has been checked
+    pub reward_vault: AccountInfo<'info>,
+    #[account(
+        init_if_needed,
+        payer = funder,
+        associated_token::mint = stake_token,
+        associated_token::authority = reward_vault,
+    )]
+    pub reward_vault_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(address = liquid_lockup.receipt_mint)]
+    pub receipt_mint: Box<Account<'info, Mint>>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Adds the creator's reward to the pool, raising the amount each outstanding
+/// receipt redeems for.
+pub fn handler(ctx: Context<FundLiquidRewards>, amount: u64) -> Result<()> {
+    let liquid_lockup = &mut ctx.accounts.liquid_lockup;
+    let funder = &ctx.accounts.funder;
+    let receipt_supply = ctx.accounts.receipt_mint.supply;
+
+    // Rewards set the exchange rate, so only the creator may add them.
+    require!(
+        liquid_lockup.creator == funder.key(),
+        ZbcnStakeError::UnAuthorized
+    );
+    require!(amount > 0, ZbcnStakeError::InvalidAmount);
+    // With no receipts outstanding the reward would go to the next staker.
+    require!(receipt_supply > 0, ZbcnStakeError::LiquidLockupEmpty);
+
+    transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.funder_token_account.to_account_info(),
+                to: ctx.accounts.reward_vault_token_account.to_account_info(),
+                authority: funder.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    liquid_lockup.record_reward(amount)?;
+
+    emit!(LiquidRewardsFunded {
+        liquid_lockup: liquid_lockup.key(),
+        funder: funder.key(),
+        amount,
+        total_staked: liquid_lockup.total_staked,
+        total_rewards: liquid_lockup.total_rewards,
+        receipt_supply,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,77 @@
+use crate::{
+    constants::REWARD_VAULT, error::ZbcnStakeError, events::RewardsFunded, Lockup, LOCKUP,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{transfer, Mint, Token, TokenAccount, Transfer},
+};
+
+#[derive(Accounts)]
+pub struct FundRewards<'info> {
+    #[account(mut)]
+    pub funder: Signer<'info>,
+    #[account(
+        seeds = [LOCKUP.as_bytes(), lockup.stake_info.name.as_bytes()],
+        bump,
+        constraint = lockup.to_account_info().data_len() == 8 + Lockup::INIT_SPACE
+            @ ZbcnStakeError::LockupNotMigrated
+    )]
+    pub lockup: Box<Account<'info, Lockup>>,
+    #[account(
+        constraint = reward_token.key() == lockup.reward_token.token_address
+            @ ZbcnStakeError::InvalidRewardToken
+    )]
+    pub reward_token: Box<Account<'info, Mint>>,
+    #[account(
+        mut,
+        token::mint = reward_token,
+        token::authority = funder,
+    )]
+    pub funder_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        seeds = [REWARD_VAULT.as_bytes(), lockup.key().as_ref()],
+        bump
+    )]
+    /// CHECK: seeds has been checked
+    pub reward_vault: AccountInfo<'info>,
+    #[account(
+        init_if_needed,
+        payer = funder,
+        associated_token::mint = reward_token,
+        associated_token::authority = reward_vault,
+    )]
+    pub reward_vault_token_account: Box<Account<'info, TokenAccount>>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<FundRewards>, amount: u64) -> Result<()> {
+    let lockup = &ctx.accounts.lockup;
+    let funder = &ctx.accounts.funder;
+    let reward_vault_token_account = &mut ctx.accounts.reward_vault_token_account;
+
+    require!(amount > 0, ZbcnStakeError::InvalidAmount);
+
+    let cpi_ctx = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.funder_token_account.to_account_info(),
+            to: reward_vault_token_account.to_account_info(),
+            authority: funder.to_account_info(),
+        },
+    );
+    transfer(cpi_ctx, amount)?;
+    reward_vault_token_account.reload()?;
+
+    emit!(RewardsFunded {
+        lockup: lockup.key(),
+        funder: funder.key(),
+        amount,
+        reward_vault_balance: reward_vault_token_account.amount,
+        reward_liability: lockup.stats.total_reward_liability,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,186 @@
+use crate::{
+    error::ZbcnStakeError,
+    events::StakeIncreased,
+    state::TopUpPolicy,
+    utils::{calculate_reward, mul_div, LockPeriod, Rounding},
+    Lockup, UserStakeData, LOCKUP, REWARD_VAULT, STAKE_VAULT,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{transfer, Mint, Token, TokenAccount, Transfer};
+
+#[derive(Accounts)]
+#[instruction(params: IncreaseStakeParams)]
+pub struct IncreaseStake<'info> {
+    pub staker: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [LOCKUP.as_bytes(), lockup.stake_info.name.as_bytes()],
+        bump,
+        constraint = lockup.to_account_info().data_len() == 8 + Lockup::INIT_SPACE
+            @ ZbcnStakeError::LockupNotMigrated
+    )]
+    pub lockup: Box<Account<'info, Lockup>>,
+    #[account(
+        mut,
+        seeds = [staker.key().as_ref(), lockup.key().as_ref(), &params.nonce.to_le_bytes()],
+        bump
+    )]
+    pub stake_pda: Box<Account<'info, UserStakeData>>,
+    pub stake_token: Box<Account<'info, Mint>>,
+    #[account(
+        mut,
+        associated_token::mint = stake_token,
+        associated_token::authority = staker,
+    )]
+    pub staker_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        seeds = [STAKE_VAULT.as_bytes(), lockup.key().as_ref()],
+        bump
+    )]
+    /// CHECK: seeds has been checked
+    pub stake_vault: AccountInfo<'info>,
+    #[account(
+        mut,
+        associated_token::mint = stake_token,
+        associated_token::authority = stake_vault,
+    )]
+    pub stake_vault_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        seeds = [REWARD_VAULT.as_bytes(), lockup.key().as_ref()],
+        bump
+    )]
+    /// CHECK: seeds has been checked
+    pub reward_vault: AccountInfo<'info>,
+    #[account(
+        associated_token::mint = lockup.reward_token.token_address,
+        associated_token::authority = reward_vault,
+    )]
+    pub reward_vault_token_account: Box<Account<'info, TokenAccount>>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct IncreaseStakeParams {
+    pub nonce: u64,
+    pub amount: u64,
+}
+
+pub fn handler(ctx: Context<IncreaseStake>, params: IncreaseStakeParams) -> Result<()> {
+    let lockup = &mut ctx.accounts.lockup;
+    let stake_pda = &mut ctx.accounts.stake_pda;
+    let current_time = Clock::get()?.unix_timestamp;
+
+    let reward_rate = run_validations(
+        ctx.accounts.stake_token.key(),
+        lockup,
+        stake_pda,
+        params.amount,
+    )?;
+    let lock_period = stake_pda.lock_period()?;
+
+    let staked_amount = stake_pda
+        .staked_amount
+        .checked_add(params.amount)
+        .ok_or(ZbcnStakeError::MathOverflow)?;
+    let created_time = match lockup.options.top_up_policy {
+        TopUpPolicy::ResetLock => current_time,
+        TopUpPolicy::WeightedLock => {
+            let elapsed = current_time.saturating_sub(stake_pda.created_time).max(0);
+            let shift = mul_div(
+                elapsed as u64,
+                params.amount as u128,
+                staked_amount as u128,
+                Rounding::Down,
+            )?;
+            stake_pda.created_time + shift as i64
+        }
+    };
+
+    // Reward accrued and not yet claimed stays earned. For whatever remains of
+    // the lock from its new start, the existing principal keeps the rate it was
+    // opened at and only the top-up earns the rate offered now.
+    let accrued_reward = stake_pda.claimable_reward_at(current_time)?;
+    let remaining = lock_period
+        .end_time(created_time)?
+        .saturating_sub(current_time);
+    let remaining_reward = match LockPeriod::from_seconds(remaining) {
+        Ok(remaining) => calculate_reward(
+            stake_pda.staked_amount,
+            stake_pda.reward_rate,
+            remaining,
+        )?
+        .checked_add(calculate_reward(params.amount, reward_rate, remaining)?)
+        .ok_or(ZbcnStakeError::MathOverflow)?,
+        Err(_) => 0,
+    };
+    let reward_amount = accrued_reward
+        .checked_add(remaining_reward)
+        .ok_or(ZbcnStakeError::MathOverflow)?;
+    // Rounds down in the vault's favour, as when positions are merged.
+    let weighted_rate = (stake_pda.staked_amount as u128 * stake_pda.reward_rate as u128)
+        .checked_add(params.amount as u128 * reward_rate as u128)
+        .ok_or(ZbcnStakeError::MathOverflow)?;
+    let reward_rate = mul_div(1, weighted_rate, staked_amount as u128, Rounding::Down)?;
+
+    let trns_spl = Transfer {
+        from: ctx.accounts.staker_token_account.to_account_info(),
+        to: ctx.accounts.stake_vault_token_account.to_account_info(),
+        authority: ctx.accounts.staker.to_account_info(),
+    };
+    let ctx_spl: CpiContext<'_, '_, '_, '_, _> =
+        CpiContext::new(ctx.accounts.token_program.to_account_info(), trns_spl);
+    transfer(ctx_spl, params.amount)?;
+
+    lockup.record_top_up(params.amount, stake_pda.unclaimed_reward(), reward_amount)?;
+    lockup.ensure_reward_coverage(ctx.accounts.reward_vault_token_account.amount)?;
+
+    stake_pda.staked_amount = staked_amount;
+    stake_pda.created_time = created_time;
+    stake_pda.reward_rate = reward_rate;
+    stake_pda.reward_amount = reward_amount;
+    stake_pda.reward_claimed_so_far = 0;
+    stake_pda.vested_reward = accrued_reward;
+    // A weighted start lies in the past; what is left accrues from now on.
+    stake_pda.reward_start_time = current_time;
+
+    emit!(StakeIncreased {
+        staker: stake_pda.staker,
+        lockup: lockup.key(),
+        nonce: params.nonce,
+        amount: params.amount,
+        staked_amount,
+        created_time,
+        reward_amount,
+    });
+
+    Ok(())
+}
+
+/// Returns the annual reward rate currently offered for the position's lock period.
+fn run_validations(
+    stake_token: Pubkey,
+    lockup: &Lockup,
+    stake_pda: &UserStakeData,
+    amount: u64,
+) -> Result<u64> {
+    if stake_pda.stake_claimed {
+        return Err(ZbcnStakeError::StakeAlreadyClaimed.into());
+    }
+    require!(
+        !stake_pda.is_unbonding(),
+        ZbcnStakeError::UnstakeAlreadyRequested
+    );
+    require!(
+        !stake_pda.is_tokenized(),
+        ZbcnStakeError::PositionTokenized
+    );
+
+    lockup.require_staking_open()?;
+    require!(
+        stake_token == lockup.staked_token.token_address,
+        ZbcnStakeError::InvalidStakeToken
+    );
+    require!(amount > 0, ZbcnStakeError::InvalidAmount);
+
+    lockup.offered_reward_rate(stake_pda.lock_period()?)
+}
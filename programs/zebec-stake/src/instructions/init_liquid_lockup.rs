@@ -0,0 +1,78 @@
+use crate::{
+    events::LiquidLockupInitialized, state::LockupRoles, LiquidLockup, LIQUID_LOCKUP, RECEIPT_MINT,
+    REWARD_VAULT, STAKE_VAULT,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token};
+
+#[derive(Accounts)]
+#[instruction(params: InitLiquidLockupParams)]
+pub struct InitLiquidLockup<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + LiquidLockup::INIT_SPACE,
+        seeds = [LIQUID_LOCKUP.as_bytes(), params.name.as_bytes()],
+        bump
+    )]
+    pub liquid_lockup: Box<Account<'info, LiquidLockup>>,
+    #[account(
+        init,
+        payer = creator,
+        space = 0,
+        seeds = [STAKE_VAULT.as_bytes(), liquid_lockup.key().as_ref()],
+        bump
+    )]
+    /// CHECK: seeds has been checked
+    pub stake_vault: AccountInfo<'info>,
+    #[account(
+        init,
+        payer = creator,
+        space = 0,
+        seeds = [REWARD_VAULT.as_bytes(), liquid_lockup.key().as_ref()],
+        bump
+    )]
+    /// CHECK: seeds has been checked
+    pub reward_vault: AccountInfo<'info>,
+    pub stake_token: Box<Account<'info, Mint>>,
+    #[account(
+        init,
+        payer = creator,
+        mint::decimals = stake_token.decimals,
+        mint::authority = liquid_lockup,
+        seeds = [RECEIPT_MINT.as_bytes(), liquid_lockup.key().as_ref()],
+        bump
+    )]
+    pub receipt_mint: Box<Account<'info, Mint>>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct InitLiquidLockupParams {
+    pub name: String,
+    pub minimum_stake: u64,
+}
+
+pub fn handler(ctx: Context<InitLiquidLockup>, params: InitLiquidLockupParams) -> Result<()> {
+    let liquid_lockup = &mut ctx.accounts.liquid_lockup;
+    let creator = ctx.accounts.creator.key();
+
+    liquid_lockup.name = params.name;
+    liquid_lockup.creator = creator;
+    liquid_lockup.staked_token = ctx.accounts.stake_token.key();
+    liquid_lockup.receipt_mint = ctx.accounts.receipt_mint.key();
+    liquid_lockup.minimum_stake = params.minimum_stake;
+    liquid_lockup.roles = LockupRoles::new(creator);
+
+    emit!(LiquidLockupInitialized {
+        name: liquid_lockup.name.clone(),
+        creator,
+        liquid_lockup: liquid_lockup.key(),
+        receipt_mint: liquid_lockup.receipt_mint,
+    });
+
+    Ok(())
+}
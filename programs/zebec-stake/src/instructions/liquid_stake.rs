@@ -0,0 +1,117 @@
+use crate::{
+    error::ZbcnStakeError, events::LiquidStaked, LiquidLockup, LIQUID_LOCKUP, STAKE_VAULT,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{mint_to, transfer, Mint, MintTo, Token, TokenAccount, Transfer},
+};
+
+#[derive(Accounts)]
+pub struct LiquidStake<'info> {
+    pub staker: Signer<'info>,
+    #[account(mut)]
+    pub fee_payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [LIQUID_LOCKUP.as_bytes(), liquid_lockup.name.as_bytes()],
+        bump
+    )]
+    pub liquid_lockup: Box<Account<'info, LiquidLockup>>,
+    #[account(
+        constraint = stake_token.key() == liquid_lockup.staked_token
+            @ ZbcnStakeError::InvalidStakeToken
+    )]
+    pub stake_token: Box<Account<'info, Mint>>,
+    #[account(
+        mut,
+        associated_token::mint = stake_token,
+        associated_token::authority = staker,
+    )]
+    pub staker_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        seeds = [STAKE_VAULT.as_bytes(), liquid_lockup.key().as_ref()],
+        bump
+    )]
+    /// CHECK: seeds has been checked
+    pub stake_vault: AccountInfo<'info>,
+    #[account(
+        init_if_needed,
+        payer = fee_payer,
+        associated_token::mint = stake_token,
+        associated_token::authority = stake_vault,
+    )]
+    pub stake_vault_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        address = liquid_lockup.receipt_mint,
+    )]
+    pub receipt_mint: Box<Account<'info, Mint>>,
+    #[account(
+        init_if_needed,
+        payer = fee_payer,
+        associated_token::mint = receipt_mint,
+        associated_token::authority = staker,
+    )]
+    pub staker_receipt_account: Box<Account<'info, TokenAccount>>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Deposits `amount` into the pool and mints receipts at the current
+/// exchange rate.
+pub fn handler(ctx: Context<LiquidStake>, amount: u64) -> Result<()> {
+    let liquid_lockup = &mut ctx.accounts.liquid_lockup;
+    let staker = &ctx.accounts.staker;
+    let token_program = &ctx.accounts.token_program;
+
+    liquid_lockup.require_staking_open()?;
+    require!(
+        amount >= liquid_lockup.minimum_stake,
+        ZbcnStakeError::MinimumStakeNotMet
+    );
+    let receipt_amount =
+        liquid_lockup.receipts_for_deposit(amount, ctx.accounts.receipt_mint.supply)?;
+    require!(receipt_amount > 0, ZbcnStakeError::InvalidAmount);
+
+    transfer(
+        CpiContext::new(
+            token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.staker_token_account.to_account_info(),
+                to: ctx.accounts.stake_vault_token_account.to_account_info(),
+                authority: staker.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    mint_to(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            MintTo {
+                mint: ctx.accounts.receipt_mint.to_account_info(),
+                to: ctx.accounts.staker_receipt_account.to_account_info(),
+                authority: liquid_lockup.to_account_info(),
+            },
+            &[&[
+                LIQUID_LOCKUP.as_bytes(),
+                liquid_lockup.name.as_bytes(),
+                &[ctx.bumps.liquid_lockup],
+            ]],
+        ),
+        receipt_amount,
+    )?;
+
+    liquid_lockup.record_deposit(amount)?;
+
+    emit!(LiquidStaked {
+        staker: staker.key(),
+        liquid_lockup: liquid_lockup.key(),
+        amount,
+        receipt_amount,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,142 @@
+use crate::{
+    error::ZbcnStakeError, events::LiquidUnstaked, state::LockupState, utils::transfer_from_vault,
+    LiquidLockup, LIQUID_LOCKUP, REWARD_VAULT, STAKE_VAULT,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{burn, Burn, Mint, Token, TokenAccount},
+};
+
+#[derive(Accounts)]
+pub struct LiquidUnstake<'info> {
+    pub staker: Signer<'info>,
+    #[account(mut)]
+    pub fee_payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [LIQUID_LOCKUP.as_bytes(), liquid_lockup.name.as_bytes()],
+        bump
+    )]
+    pub liquid_lockup: Box<Account<'info, LiquidLockup>>,
+    #[account(
+        constraint = stake_token.key() == liquid_lockup.staked_token
+            @ ZbcnStakeError::InvalidStakeToken
+    )]
+    pub stake_token: Box<Account<'info, Mint>>,
+    #[account(
+        init_if_needed,
+        payer = fee_payer,
+        associated_token::mint = stake_token,
+        associated_token::authority = staker,
+    )]
+    pub staker_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        seeds = [STAKE_VAULT.as_bytes(), liquid_lockup.key().as_ref()],
+        bump
+    )]
+    /// CHECK: seeds has been checked
+    pub stake_vault: AccountInfo<'info>,
+    #[account(
+        mut,
+        associated_token::mint = stake_token,
+        associated_token::authority = stake_vault,
+    )]
+    pub stake_vault_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        seeds = [REWARD_VAULT.as_bytes(), liquid_lockup.key().as_ref()],
+        bump
+    )]
+    /// CHECK: seeds has been checked
+    pub reward_vault: AccountInfo<'info>,
+    #[account(
+        init_if_needed,
+        payer = fee_payer,
+        associated_token::mint = stake_token,
+        associated_token::authority = reward_vault,
+    )]
+    pub reward_vault_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        address = liquid_lockup.receipt_mint,
+    )]
+    pub receipt_mint: Box<Account<'info, Mint>>,
+    #[account(
+        mut,
+        associated_token::mint = receipt_mint,
+        associated_token::authority = staker,
+    )]
+    pub staker_receipt_account: Box<Account<'info, TokenAccount>>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Burns `receipt_amount` receipts and pays their share of the pool's
+/// principal and funded reward.
+pub fn handler(ctx: Context<LiquidUnstake>, receipt_amount: u64) -> Result<()> {
+    let liquid_lockup = &mut ctx.accounts.liquid_lockup;
+    let staker = &ctx.accounts.staker;
+    let token_program = &ctx.accounts.token_program;
+
+    require!(
+        liquid_lockup.state != LockupState::Paused,
+        ZbcnStakeError::LockupPaused
+    );
+    let (principal_amount, reward_amount) =
+        liquid_lockup.redemption_for(receipt_amount, ctx.accounts.receipt_mint.supply)?;
+
+    burn(
+        CpiContext::new(
+            token_program.to_account_info(),
+            Burn {
+                mint: ctx.accounts.receipt_mint.to_account_info(),
+                from: ctx.accounts.staker_receipt_account.to_account_info(),
+                authority: staker.to_account_info(),
+            },
+        ),
+        receipt_amount,
+    )?;
+
+    let liquid_lockup_key = liquid_lockup.key();
+    if principal_amount > 0 {
+        transfer_from_vault(
+            token_program.to_account_info(),
+            ctx.accounts.stake_vault_token_account.to_account_info(),
+            ctx.accounts.staker_token_account.to_account_info(),
+            ctx.accounts.stake_vault.to_account_info(),
+            &[
+                STAKE_VAULT.as_bytes(),
+                liquid_lockup_key.as_ref(),
+                &[ctx.bumps.stake_vault],
+            ],
+            principal_amount,
+        )?;
+    }
+    if reward_amount > 0 {
+        transfer_from_vault(
+            token_program.to_account_info(),
+            ctx.accounts.reward_vault_token_account.to_account_info(),
+            ctx.accounts.staker_token_account.to_account_info(),
+            ctx.accounts.reward_vault.to_account_info(),
+            &[
+                REWARD_VAULT.as_bytes(),
+                liquid_lockup_key.as_ref(),
+                &[ctx.bumps.reward_vault],
+            ],
+            reward_amount,
+        )?;
+    }
+
+    liquid_lockup.record_redemption(principal_amount, reward_amount)?;
+
+    emit!(LiquidUnstaked {
+        staker: staker.key(),
+        liquid_lockup: liquid_lockup_key,
+        receipt_amount,
+        principal_amount,
+        reward_amount,
+    });
+
+    Ok(())
+}
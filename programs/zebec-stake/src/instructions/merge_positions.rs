@@ -0,0 +1,152 @@
+use crate::{
+    error::ZbcnStakeError,
+    events::PositionsMerged,
+    utils::{mul_div, Rounding},
+    Lockup, UserStakeData, LOCKUP,
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+#[instruction(nonce: u64)]
+pub struct MergePositions<'info> {
+    pub staker: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [LOCKUP.as_bytes(), lockup.stake_info.name.as_bytes()],
+        bump,
+        constraint = lockup.to_account_info().data_len() == 8 + Lockup::INIT_SPACE
+            @ ZbcnStakeError::LockupNotMigrated
+    )]
+    pub lockup: Box<Account<'info, Lockup>>,
+    #[account(
+        mut,
+        seeds = [staker.key().as_ref(), lockup.key().as_ref(), &nonce.to_le_bytes()],
+        bump
+    )]
+    pub stake_pda: Box<Account<'info, UserStakeData>>,
+}
+
+/// Folds positions into `stake_pda`. `remaining_accounts` holds pairs of a
+/// position to merge followed by its recorded rent payer, which receives the
+/// rent of the closed position.
+///
+/// All positions must be open and share the lock period. The merged position
+/// matures with the latest of them, is owed the sum of their unclaimed
+/// rewards and carries the principal-weighted average of their reward rates,
+/// fees and penalties.
+// `usize::is_multiple_of` is newer than the rustc shipped with the SBF toolchain.
+#[allow(clippy::manual_is_multiple_of)]
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, MergePositions<'info>>,
+    nonce: u64,
+) -> Result<()> {
+    let lockup = &mut ctx.accounts.lockup;
+    let stake_pda = &mut ctx.accounts.stake_pda;
+    let staker = ctx.accounts.staker.key();
+    let current_time = Clock::get()?.unix_timestamp;
+
+    lockup.require_staking_open()?;
+    require!(
+        !stake_pda.stake_claimed,
+        ZbcnStakeError::StakeAlreadyClaimed
+    );
+    require!(
+        !stake_pda.is_unbonding(),
+        ZbcnStakeError::UnstakeAlreadyRequested
+    );
+    require!(
+        !stake_pda.is_tokenized(),
+        ZbcnStakeError::PositionTokenized
+    );
+    require!(
+        !ctx.remaining_accounts.is_empty() && ctx.remaining_accounts.len() % 2 == 0,
+        ZbcnStakeError::PositionMismatch
+    );
+
+    let mut staked_amount = stake_pda.staked_amount as u128;
+    let mut weighted_rate = stake_pda.staked_amount as u128 * stake_pda.reward_rate as u128;
+    let mut weighted_fee = stake_pda.staked_amount as u128 * stake_pda.fee as u128;
+    let mut weighted_penalty = stake_pda.staked_amount as u128 * stake_pda.penalty as u128;
+    let mut reward_amount = stake_pda.unclaimed_reward();
+    let mut vested_reward = stake_pda.claimable_reward_at(current_time)?;
+    let mut created_time = stake_pda.created_time;
+    let mut cooldown_seconds = stake_pda.cooldown_seconds;
+    let count = ctx.remaining_accounts.len() / 2;
+    let mut merged: Vec<Pubkey> = Vec::with_capacity(count);
+    let mut merged_nonces = Vec::with_capacity(count);
+
+    for accounts in ctx.remaining_accounts.chunks(2) {
+        let (info, rent_payer) = (&accounts[0], &accounts[1]);
+        require!(
+            info.key() != stake_pda.key() && !merged.contains(&info.key()),
+            ZbcnStakeError::PositionMismatch
+        );
+        let position = Account::<UserStakeData>::try_from(info)?;
+        require!(
+            position.staker == staker && position.lockup == lockup.key(),
+            ZbcnStakeError::InvalidStaker
+        );
+        require!(!position.stake_claimed, ZbcnStakeError::StakeAlreadyClaimed);
+        require!(
+            !position.is_unbonding(),
+            ZbcnStakeError::UnstakeAlreadyRequested
+        );
+        require!(
+            !position.is_tokenized(),
+            ZbcnStakeError::PositionTokenized
+        );
+        require!(
+            position.lock_period == stake_pda.lock_period,
+            ZbcnStakeError::PositionMismatch
+        );
+        require_keys_eq!(
+            position.rent_payer,
+            rent_payer.key(),
+            ZbcnStakeError::InvalidRentPayer
+        );
+
+        staked_amount += position.staked_amount as u128;
+        weighted_rate += position.staked_amount as u128 * position.reward_rate as u128;
+        weighted_fee += position.staked_amount as u128 * position.fee as u128;
+        weighted_penalty += position.staked_amount as u128 * position.penalty as u128;
+        reward_amount = reward_amount
+            .checked_add(position.unclaimed_reward())
+            .ok_or(ZbcnStakeError::MathOverflow)?;
+        vested_reward = vested_reward
+            .checked_add(position.claimable_reward_at(current_time)?)
+            .ok_or(ZbcnStakeError::MathOverflow)?;
+        created_time = created_time.max(position.created_time);
+        cooldown_seconds = cooldown_seconds.max(position.cooldown_seconds);
+        merged.push(info.key());
+        merged_nonces.push(position.nonce);
+
+        position.close(rent_payer.clone())?;
+    }
+
+    let staked_amount = u64::try_from(staked_amount).map_err(|_| ZbcnStakeError::MathOverflow)?;
+    // Rate rounds down and fee and penalty round up, all in the vault's favour.
+    stake_pda.reward_rate = mul_div(1, weighted_rate, staked_amount as u128, Rounding::Down)?;
+    stake_pda.fee = mul_div(1, weighted_fee, staked_amount as u128, Rounding::Up)?;
+    stake_pda.penalty = mul_div(1, weighted_penalty, staked_amount as u128, Rounding::Up)?;
+    stake_pda.staked_amount = staked_amount;
+    stake_pda.reward_amount = reward_amount;
+    stake_pda.reward_claimed_so_far = 0;
+    stake_pda.vested_reward = vested_reward;
+    stake_pda.created_time = created_time;
+    stake_pda.cooldown_seconds = cooldown_seconds;
+
+    lockup.record_merge(merged.len() as u64)?;
+
+    emit!(PositionsMerged {
+        staker,
+        lockup: lockup.key(),
+        nonce,
+        merged_nonces,
+        staked_amount,
+        created_time,
+        reward_rate: stake_pda.reward_rate,
+        reward_amount,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,74 @@
+use crate::{
+    error::ZbcnStakeError,
+    events::LockupMigrated,
+    state::{LegacyLockup, Lockup, LockupRoles},
+    utils::resize_account,
+    LOCKUP,
+};
+use anchor_lang::{prelude::*, Discriminator};
+
+#[derive(Accounts)]
+pub struct MigrateLockup<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    #[account(mut, owner = crate::ID)]
+    /// CHECK: legacy layout is deserialized and its seeds checked in the handler
+    pub lockup: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<MigrateLockup>) -> Result<()> {
+    let lockup = &ctx.accounts.lockup;
+    let creator = &ctx.accounts.creator;
+
+    let legacy = {
+        let data = lockup.try_borrow_data()?;
+        require!(
+            data.len() == 8 + LegacyLockup::INIT_SPACE,
+            ZbcnStakeError::LockupAlreadyMigrated
+        );
+        require!(
+            data[..8] == *Lockup::DISCRIMINATOR,
+            ErrorCode::AccountDiscriminatorMismatch
+        );
+        LegacyLockup::deserialize(&mut &data[8..])?
+    };
+
+    let (lockup_address, _) = Pubkey::find_program_address(
+        &[LOCKUP.as_bytes(), legacy.stake_info.name.as_bytes()],
+        ctx.program_id,
+    );
+    require_keys_eq!(lockup_address, lockup.key(), ErrorCode::ConstraintSeeds);
+    require!(
+        legacy.stake_info.creator == creator.key(),
+        ZbcnStakeError::UnAuthorized
+    );
+
+    // Counters start empty; `migrate_stake` fills them in as each legacy
+    // position is migrated, so excess rewards stay locked until the operator
+    // completes the migration. Every role starts with the existing creator.
+    let migrated = Lockup {
+        roles: LockupRoles::new(legacy.stake_info.creator),
+        stake_info: legacy.stake_info,
+        reward_token: legacy.reward_token,
+        staked_token: legacy.staked_token,
+        fee_info: legacy.fee_info,
+        legacy_migration_pending: true,
+        ..Lockup::default()
+    };
+
+    resize_account(
+        lockup,
+        &creator.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        8 + Lockup::INIT_SPACE,
+    )?;
+    migrated.try_serialize(&mut &mut lockup.try_borrow_mut_data()?[..])?;
+
+    emit!(LockupMigrated {
+        lockup: lockup.key(),
+        total_staked: migrated.staked_token.total_staked,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,146 @@
+use crate::{
+    error::ZbcnStakeError,
+    events::StakeMigrated,
+    state::{LegacyUserStakeData, Lockup, LockupRole, UserStakeData},
+    utils::{calculate_reward, resize_account, LockPeriod},
+    LOCKUP,
+};
+use anchor_lang::{prelude::*, Discriminator};
+
+#[derive(Accounts)]
+#[instruction(nonce: u64)]
+pub struct MigrateStake<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [LOCKUP.as_bytes(), lockup.stake_info.name.as_bytes()],
+        bump,
+        constraint = lockup.to_account_info().data_len() == 8 + Lockup::INIT_SPACE
+            @ ZbcnStakeError::LockupNotMigrated
+    )]
+    pub lockup: Box<Account<'info, Lockup>>,
+    /// CHECK: only used to derive the stake pda
+    pub staker: AccountInfo<'info>,
+    #[account(
+        mut,
+        owner = crate::ID,
+        seeds = [staker.key().as_ref(), lockup.key().as_ref(), nonce.to_le_bytes().as_ref()],
+        bump
+    )]
+    /// CHECK: legacy layout is deserialized manually in the handler
+    pub stake_pda: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<MigrateStake>, nonce: u64) -> Result<()> {
+    let lockup = &mut ctx.accounts.lockup;
+    let authority = &ctx.accounts.authority;
+    let stake_pda = &ctx.accounts.stake_pda;
+
+    lockup.require_role(LockupRole::Operator, authority.key())?;
+
+    let legacy = {
+        let data = stake_pda.try_borrow_data()?;
+        require!(
+            data.len() == 8 + LegacyUserStakeData::INIT_SPACE,
+            ZbcnStakeError::StakeAlreadyMigrated
+        );
+        require!(
+            data[..8] == *UserStakeData::DISCRIMINATOR,
+            ErrorCode::AccountDiscriminatorMismatch
+        );
+        LegacyUserStakeData::deserialize(&mut &data[8..])?
+    };
+
+    // Some legacy records hold the lock period as a day count; accept whichever
+    // reading matches an offered duration and store it in seconds.
+    let offered = [
+        LockPeriod::from_seconds(legacy.lock_period),
+        LockPeriod::from_days(legacy.lock_period),
+    ]
+    .into_iter()
+    .flatten()
+    .find_map(|lock_period| {
+        lockup
+            .get_reward_for_duration(lock_period.duration_key())
+            .map(|reward_rate| (lock_period, reward_rate))
+    });
+
+    // A non-positive legacy period is no lock at all; store a one second period
+    // that ends at once so the principal can leave, free of reward and fee.
+    let is_lock_valid = legacy.lock_period > 0;
+    let fallback_period = if is_lock_valid { legacy.lock_period } else { 1 };
+
+    let (lock_period, reward_rate, reward_amount) = match offered {
+        Some((lock_period, reward_rate)) if legacy.stake_claimed => {
+            (lock_period.as_seconds(), reward_rate, legacy.reward_amount)
+        }
+        Some((lock_period, reward_rate)) => (
+            lock_period.as_seconds(),
+            reward_rate,
+            calculate_reward(legacy.staked_amount, reward_rate, lock_period)?,
+        ),
+        None if legacy.stake_claimed => (fallback_period, 0, legacy.reward_amount),
+        // The duration has since been removed from the lockup; migrate at a zero
+        // rate so the principal is not stranded in the legacy layout.
+        None => (fallback_period, 0, 0),
+    };
+
+    let migrated = UserStakeData {
+        nonce: legacy.nonce,
+        created_time: legacy.created_time,
+        staked_amount: legacy.staked_amount,
+        reward_amount,
+        stake_claimed: legacy.stake_claimed,
+        lock_period,
+        staker: legacy.staker,
+        lockup: legacy.lockup,
+        reward_rate,
+        fee: if is_lock_valid {
+            lockup.fee_info.fee
+        } else {
+            0
+        },
+        penalty: lockup.penalty_for(LockPeriod::from_seconds(lock_period)?),
+        // The original rent payer was never recorded; the staker gets it back.
+        rent_payer: legacy.staker,
+        ..UserStakeData::default()
+    };
+
+    // Legacy stakes were added to `total_staked` but never removed on unstake,
+    // so migrating every legacy position reconciles the lockup counters.
+    if migrated.stake_claimed {
+        lockup.staked_token.total_staked = lockup
+            .staked_token
+            .total_staked
+            .saturating_sub(migrated.staked_amount);
+    } else {
+        lockup.stats.active_positions = lockup
+            .stats
+            .active_positions
+            .checked_add(1)
+            .ok_or(ZbcnStakeError::MathOverflow)?;
+        lockup.add_reward_liability(migrated.reward_amount)?;
+    }
+
+    resize_account(
+        stake_pda,
+        &authority.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        8 + UserStakeData::INIT_SPACE,
+    )?;
+    migrated.try_serialize(&mut &mut stake_pda.try_borrow_mut_data()?[..])?;
+
+    emit!(StakeMigrated {
+        staker: migrated.staker,
+        lockup: migrated.lockup,
+        nonce,
+        lock_period: migrated.lock_period,
+        reward_rate: migrated.reward_rate,
+        fee: migrated.fee,
+        reward_amount: migrated.reward_amount,
+    });
+
+    Ok(())
+}
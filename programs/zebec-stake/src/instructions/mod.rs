@@ -1,11 +1,81 @@
 pub mod init_lockup;
+pub mod migrate_lockup;
+pub mod migrate_stake;
 pub mod stake;
 pub mod unstake;
 pub mod whitelist_staker;
 pub mod update_lockup;
+pub mod fund_rewards;
+pub mod withdraw_excess_rewards;
+pub mod propose_authority;
+pub mod accept_authority;
+pub mod grant_role;
+pub mod revoke_role;
+pub mod set_lockup_state;
+pub mod queue_lockup_update;
+pub mod execute_lockup_update;
+pub mod cancel_lockup_update;
+pub mod partial_unstake;
+pub mod set_lockup_options;
+pub mod increase_stake;
+pub mod extend_lock;
+pub mod merge_positions;
+pub mod close_position;
+pub mod set_penalty_schedule;
+pub mod early_unstake;
+pub mod set_auto_renew;
+pub mod crank_renew;
+pub mod compound;
+pub mod claim_rewards;
+pub mod request_unstake;
+pub mod withdraw;
+pub mod transfer_position;
+pub mod init_liquid_lockup;
+pub mod liquid_stake;
+pub mod liquid_unstake;
+pub mod fund_liquid_rewards;
+pub mod set_delegate;
+pub mod revoke_delegate;
+pub mod complete_legacy_migration;
+pub mod set_liquid_lockup_state;
 
 pub use init_lockup::*;
+pub use migrate_lockup::*;
+pub use migrate_stake::*;
 pub use stake::*;
 pub use unstake::*;
 pub use whitelist_staker::*;
-pub use update_lockup::*;
\ No newline at end of file
+pub use update_lockup::*;
+pub use fund_rewards::*;
+pub use withdraw_excess_rewards::*;
+pub use propose_authority::*;
+pub use accept_authority::*;
+pub use grant_role::*;
+pub use revoke_role::*;
+pub use set_lockup_state::*;
+pub use queue_lockup_update::*;
+pub use execute_lockup_update::*;
+pub use cancel_lockup_update::*;
+pub use partial_unstake::*;
+pub use set_lockup_options::*;
+pub use increase_stake::*;
+pub use extend_lock::*;
+pub use merge_positions::*;
+pub use close_position::*;
+pub use set_penalty_schedule::*;
+pub use early_unstake::*;
+pub use set_auto_renew::*;
+pub use crank_renew::*;
+pub use compound::*;
+pub use claim_rewards::*;
+pub use request_unstake::*;
+pub use withdraw::*;
+pub use transfer_position::*;
+pub use init_liquid_lockup::*;
+pub use liquid_stake::*;
+pub use liquid_unstake::*;
+pub use fund_liquid_rewards::*;
+pub use set_delegate::*;
+pub use revoke_delegate::*;
+pub use complete_legacy_migration::*;
+pub use set_liquid_lockup_state::*;
\ No newline at end of file
@@ -0,0 +1,268 @@
+use crate::{
+    constants::REWARD_VAULT,
+    error::ZbcnStakeError,
+    events::PartiallyUnstaked,
+    state::LockupState,
+    utils::{calculate_fee, calculate_reward, mul_div, transfer_from_vault, LockPeriod, Rounding},
+    Lockup, UserStakeData, LOCKUP, STAKE_VAULT,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount},
+};
+
+#[derive(Accounts)]
+#[instruction(params: PartialUnstakeParams)]
+pub struct PartialUnstake<'info> {
+    #[account(mut)]
+    pub staker: Signer<'info>,
+    #[account(mut)]
+    pub fee_payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [LOCKUP.as_bytes(), lockup.stake_info.name.as_bytes()],
+        bump,
+        constraint = lockup.to_account_info().data_len() == 8 + Lockup::INIT_SPACE
+            @ ZbcnStakeError::LockupNotMigrated
+    )]
+    pub lockup: Box<Account<'info, Lockup>>,
+    #[account(
+        mut,
+        seeds = [staker.key().as_ref(), lockup.key().as_ref(), &params.nonce.to_le_bytes()],
+        bump
+    )]
+    pub stake_pda: Box<Account<'info, UserStakeData>>,
+    pub reward_token: Box<Account<'info, Mint>>,
+    pub stake_token: Box<Account<'info, Mint>>,
+    #[account(
+        init_if_needed,
+        payer = fee_payer,
+        associated_token::mint = stake_token,
+        associated_token::authority = staker,
+    )]
+    pub staker_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = fee_payer,
+        associated_token::mint = reward_token,
+        associated_token::authority = staker,
+    )]
+    pub staker_reward_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        seeds = [STAKE_VAULT.as_bytes(), lockup.key().as_ref()],
+        bump
+    )]
+    /// CHECK: seeds has been checked
+    pub stake_vault: AccountInfo<'info>,
+    #[account(
+        seeds = [REWARD_VAULT.as_bytes(), lockup.key().as_ref()],
+        bump
+    )]
+    /// CHECK: seeds has been checked
+    pub reward_vault: AccountInfo<'info>,
+    #[account(
+        mut,
+        associated_token::mint = stake_token,
+        associated_token::authority = stake_vault,
+    )]
+    pub stake_vault_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = reward_token,
+        associated_token::authority = reward_vault,
+    )]
+    pub reward_vault_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = stake_token,
+        associated_token::authority = lockup.fee_info.fee_vault,
+    )]
+    pub fee_vault_token_account: Box<Account<'info, TokenAccount>>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct PartialUnstakeParams {
+    pub nonce: u64,
+    /// Principal to withdraw; the rest stays staked.
+    pub amount: u64,
+    /// Lock period in seconds for the remaining principal.
+    pub lock_period: i64,
+}
+
+pub fn handler(ctx: Context<PartialUnstake>, params: PartialUnstakeParams) -> Result<()> {
+    let lockup = &mut ctx.accounts.lockup;
+    let stake_pda = &mut ctx.accounts.stake_pda;
+    let current_time = Clock::get()?.unix_timestamp;
+
+    let lock_period = LockPeriod::from_seconds(params.lock_period)?;
+    let remaining_amount = stake_pda
+        .staked_amount
+        .checked_sub(params.amount)
+        .ok_or(ZbcnStakeError::InvalidAmount)?;
+    let reward_rate = run_validations(
+        ctx.accounts.reward_token.key(),
+        ctx.accounts.stake_token.key(),
+        lockup,
+        stake_pda,
+        params.amount,
+        remaining_amount,
+        lock_period,
+        current_time,
+    )?;
+
+    // The withdrawn share takes its pro-rata part of the matured reward and
+    // pays the fee captured at stake time; the rest of the reward stays owed
+    // to the position as vested, on top of what the remainder earns under the
+    // new lock.
+    let unclaimed_reward = stake_pda.unclaimed_reward();
+    let reward_amount = mul_div(
+        unclaimed_reward,
+        params.amount as u128,
+        stake_pda.staked_amount as u128,
+        Rounding::Down,
+    )?;
+    let fee_amount = calculate_fee(params.amount, stake_pda.fee)?;
+    let unstake_amount = params
+        .amount
+        .checked_sub(fee_amount)
+        .ok_or(ZbcnStakeError::MathOverflow)?;
+    let carried_reward = unclaimed_reward - reward_amount;
+    let new_reward = calculate_reward(remaining_amount, reward_rate, lock_period)?;
+
+    let lockup_key = lockup.key();
+    let token_program = ctx.accounts.token_program.to_account_info();
+    let stake_vault_seeds: &[&[u8]] = &[
+        STAKE_VAULT.as_bytes(),
+        lockup_key.as_ref(),
+        &[ctx.bumps.stake_vault],
+    ];
+
+    if reward_amount > 0 {
+        transfer_from_vault(
+            token_program.clone(),
+            ctx.accounts.reward_vault_token_account.to_account_info(),
+            ctx.accounts.staker_reward_token_account.to_account_info(),
+            ctx.accounts.reward_vault.to_account_info(),
+            &[
+                REWARD_VAULT.as_bytes(),
+                lockup_key.as_ref(),
+                &[ctx.bumps.reward_vault],
+            ],
+            reward_amount,
+        )?;
+    }
+    transfer_from_vault(
+        token_program.clone(),
+        ctx.accounts.stake_vault_token_account.to_account_info(),
+        ctx.accounts.staker_token_account.to_account_info(),
+        ctx.accounts.stake_vault.to_account_info(),
+        stake_vault_seeds,
+        unstake_amount,
+    )?;
+    if fee_amount > 0 {
+        transfer_from_vault(
+            token_program,
+            ctx.accounts.stake_vault_token_account.to_account_info(),
+            ctx.accounts.fee_vault_token_account.to_account_info(),
+            ctx.accounts.stake_vault.to_account_info(),
+            stake_vault_seeds,
+            fee_amount,
+        )?;
+    }
+
+    stake_pda.staked_amount = remaining_amount;
+    stake_pda.created_time = current_time;
+    stake_pda.lock_period = lock_period.as_seconds();
+    stake_pda.reward_rate = reward_rate;
+    stake_pda.fee = lockup.fee_info.fee;
+    stake_pda.penalty = lockup.penalty_for(lock_period);
+    stake_pda.reward_amount = carried_reward
+        .checked_add(new_reward)
+        .ok_or(ZbcnStakeError::MathOverflow)?;
+    stake_pda.reward_claimed_so_far = 0;
+    stake_pda.vested_reward = carried_reward;
+
+    lockup.record_withdrawal(params.amount, reward_amount, fee_amount)?;
+    lockup.add_reward_liability(new_reward)?;
+
+    let reward_vault_token_account = &mut ctx.accounts.reward_vault_token_account;
+    reward_vault_token_account.reload()?;
+    lockup.ensure_reward_coverage(reward_vault_token_account.amount)?;
+
+    emit!(PartiallyUnstaked {
+        staker: stake_pda.staker,
+        lockup: lockup_key,
+        nonce: params.nonce,
+        unstake_amount,
+        reward_amount,
+        fee_amount,
+        remaining_amount,
+        remaining_reward_amount: stake_pda.reward_amount,
+        lock_period: stake_pda.lock_period,
+    });
+
+    Ok(())
+}
+
+/// Returns the annual reward rate offered for the remainder's lock period.
+#[allow(clippy::too_many_arguments)]
+fn run_validations(
+    reward_token: Pubkey,
+    stake_token: Pubkey,
+    lockup: &Lockup,
+    stake_pda: &UserStakeData,
+    amount: u64,
+    remaining_amount: u64,
+    lock_period: LockPeriod,
+    current_time: i64,
+) -> Result<u64> {
+    if stake_pda.stake_claimed {
+        return Err(ZbcnStakeError::RewardAlreadyClaimed.into());
+    }
+    require!(
+        !stake_pda.is_unbonding(),
+        ZbcnStakeError::UnstakeAlreadyRequested
+    );
+    require!(
+        !stake_pda.is_tokenized(),
+        ZbcnStakeError::PositionTokenized
+    );
+
+    // The remainder is restaked, so the lockup has to be taking new stakes.
+    lockup.require_staking_open()?;
+
+    require!(
+        reward_token == lockup.reward_token.token_address,
+        ZbcnStakeError::InvalidRewardToken
+    );
+    require!(
+        stake_token == lockup.staked_token.token_address,
+        ZbcnStakeError::InvalidStakeToken
+    );
+    require!(
+        stake_pda.is_ended_at(current_time)?,
+        ZbcnStakeError::StakeRewardNotClaimable
+    );
+    // Positions with a cooldown leave through `request_unstake`; emergency
+    // withdrawal skips it as in `unstake_zbcn`.
+    require!(
+        stake_pda.cooldown_seconds == 0 || lockup.state == LockupState::EmergencyWithdraw,
+        ZbcnStakeError::CooldownRequired
+    );
+
+    // Withdrawing everything is `unstake_zbcn`.
+    require!(
+        amount > 0 && remaining_amount > 0,
+        ZbcnStakeError::InvalidAmount
+    );
+    require!(
+        remaining_amount >= lockup.stake_info.minimum_stake,
+        ZbcnStakeError::MinimumStakeNotMet
+    );
+
+    lockup.offered_reward_rate(lock_period)
+}
@@ -0,0 +1,35 @@
+use crate::{error::ZbcnStakeError, events::AuthorityProposed, Lockup, LOCKUP};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct ProposeAuthority<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [LOCKUP.as_bytes(), lockup.stake_info.name.as_bytes()],
+        bump,
+        constraint = lockup.to_account_info().data_len() == 8 + Lockup::INIT_SPACE
+            @ ZbcnStakeError::LockupNotMigrated
+    )]
+    pub lockup: Box<Account<'info, Lockup>>,
+}
+
+pub fn handler(ctx: Context<ProposeAuthority>, new_authority: Pubkey) -> Result<()> {
+    let lockup = &mut ctx.accounts.lockup;
+    let authority = &ctx.accounts.authority;
+
+    require!(
+        lockup.stake_info.creator == authority.key(),
+        ZbcnStakeError::UnAuthorized
+    );
+
+    lockup.pending_authority = Some(new_authority);
+
+    emit!(AuthorityProposed {
+        lockup: lockup.key(),
+        authority: authority.key(),
+        pending_authority: new_authority,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,74 @@
+use crate::{
+    constants::{FEE_DENOMINATOR, LOCKUP, PENDING_UPDATE},
+    error::ZbcnStakeError,
+    events::LockupUpdateQueued,
+    state::{LockupRole, PendingLockupUpdate},
+    Lockup, UpdateLockupParams,
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct QueueLockupUpdate<'info> {
+    #[account(mut)]
+    pub updater: Signer<'info>,
+    #[account(
+        seeds = [LOCKUP.as_bytes(), lockup.stake_info.name.as_bytes()],
+        bump,
+        constraint = lockup.to_account_info().data_len() == 8 + Lockup::INIT_SPACE
+            @ ZbcnStakeError::LockupNotMigrated
+    )]
+    pub lockup: Box<Account<'info, Lockup>>,
+    #[account(
+        init,
+        payer = updater,
+        space = 8 + PendingLockupUpdate::INIT_SPACE,
+        seeds = [PENDING_UPDATE.as_bytes(), lockup.key().as_ref()],
+        bump
+    )]
+    pub pending_update: Box<Account<'info, PendingLockupUpdate>>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(
+    ctx: Context<QueueLockupUpdate>,
+    params: UpdateLockupParams,
+    update_delay: Option<i64>,
+) -> Result<()> {
+    let lockup = &ctx.accounts.lockup;
+    let updater = &ctx.accounts.updater;
+    let pending_update = &mut ctx.accounts.pending_update;
+
+    require!(params.fee <= FEE_DENOMINATOR, ZbcnStakeError::InvalidFee);
+    lockup.require_update_roles(&params, updater.key())?;
+    if let Some(update_delay) = update_delay {
+        lockup.require_role(LockupRole::ConfigAdmin, updater.key())?;
+        require!(update_delay >= 0, ZbcnStakeError::InvalidUpdateDelay);
+    }
+
+    // The delay in force when the update is queued applies, so shortening the
+    // delay is itself subject to the current one.
+    let queued_time = Clock::get()?.unix_timestamp;
+    let effective_time = queued_time
+        .checked_add(lockup.update_delay)
+        .ok_or(ZbcnStakeError::MathOverflow)?;
+
+    pending_update.lockup = lockup.key();
+    pending_update.params = params.clone();
+    pending_update.update_delay = update_delay;
+    pending_update.queued_time = queued_time;
+    pending_update.effective_time = effective_time;
+    pending_update.payer = updater.key();
+
+    emit!(LockupUpdateQueued {
+        lockup: lockup.key(),
+        updater: updater.key(),
+        fee: params.fee,
+        fee_vault: params.fee_vault,
+        minimum_stake: params.minimum_stake,
+        duration_map: params.duration_map,
+        update_delay,
+        effective_time,
+    });
+
+    Ok(())
+}
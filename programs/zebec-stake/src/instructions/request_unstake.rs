@@ -0,0 +1,151 @@
+use crate::{
+    constants::REWARD_VAULT,
+    error::ZbcnStakeError,
+    events::UnstakeRequested,
+    state::LockupState,
+    utils::{require_receipt_holder, transfer_from_vault},
+    Lockup, UserStakeData, LOCKUP,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount},
+    token_interface,
+};
+
+#[derive(Accounts)]
+#[instruction(nonce: u64)]
+pub struct RequestUnstake<'info> {
+    /// The position's staker or delegate, or the receipt holder for a
+    /// tokenized position.
+    #[account(mut)]
+    pub staker: Signer<'info>,
+    /// CHECK: address has been checked; only receives the payout
+    #[account(
+        address = stake_pda.beneficiary(staker.key()) @ ZbcnStakeError::InvalidStaker
+    )]
+    pub beneficiary: AccountInfo<'info>,
+    #[account(mut)]
+    pub fee_payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [LOCKUP.as_bytes(), lockup.stake_info.name.as_bytes()],
+        bump,
+        constraint = lockup.to_account_info().data_len() == 8 + Lockup::INIT_SPACE
+            @ ZbcnStakeError::LockupNotMigrated
+    )]
+    pub lockup: Box<Account<'info, Lockup>>,
+    #[account(
+        mut,
+        seeds = [stake_pda.staker.as_ref(), lockup.key().as_ref(), &nonce.to_le_bytes()],
+        bump,
+        constraint = stake_pda.is_tokenized() || stake_pda.can_manage(staker.key())
+            @ ZbcnStakeError::UnAuthorized
+    )]
+    pub stake_pda: Box<Account<'info, UserStakeData>>,
+    #[account(
+        constraint = reward_token.key() == lockup.reward_token.token_address
+            @ ZbcnStakeError::InvalidRewardToken
+    )]
+    pub reward_token: Box<Account<'info, Mint>>,
+    #[account(
+        init_if_needed,
+        payer = fee_payer,
+        associated_token::mint = reward_token,
+        associated_token::authority = beneficiary,
+    )]
+    pub staker_reward_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        seeds = [REWARD_VAULT.as_bytes(), lockup.key().as_ref()],
+        bump
+    )]
+    /// CHECK: seeds has been checked
+    pub reward_vault: AccountInfo<'info>,
+    #[account(
+        mut,
+        associated_token::mint = reward_token,
+        associated_token::authority = reward_vault,
+    )]
+    pub reward_vault_token_account: Box<Account<'info, TokenAccount>>,
+    /// Required for a tokenized position; the holder keeps the receipt until
+    /// `withdraw`.
+    pub receipt_mint: Option<Box<InterfaceAccount<'info, token_interface::Mint>>>,
+    pub staker_receipt_account: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Starts the cooldown of a matured position. Its reward stops here and is
+/// paid out immediately; the principal follows with `withdraw`.
+pub fn handler(ctx: Context<RequestUnstake>, nonce: u64) -> Result<()> {
+    let lockup = &mut ctx.accounts.lockup;
+    let stake_pda = &mut ctx.accounts.stake_pda;
+    let current_time = Clock::get()?.unix_timestamp;
+
+    run_validations(lockup, stake_pda, current_time)?;
+    if let Some(receipt_mint) = stake_pda.receipt_mint {
+        require_receipt_holder(
+            receipt_mint,
+            ctx.accounts.receipt_mint.as_deref(),
+            ctx.accounts.staker_receipt_account.as_deref(),
+            ctx.accounts.staker.key(),
+        )?;
+    }
+
+    let reward_amount = stake_pda.unclaimed_reward();
+    let cooldown_end_time = current_time
+        .checked_add(stake_pda.cooldown_seconds)
+        .ok_or(ZbcnStakeError::MathOverflow)?;
+
+    if reward_amount > 0 {
+        let lockup_key = lockup.key();
+        transfer_from_vault(
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.reward_vault_token_account.to_account_info(),
+            ctx.accounts.staker_reward_token_account.to_account_info(),
+            ctx.accounts.reward_vault.to_account_info(),
+            &[
+                REWARD_VAULT.as_bytes(),
+                lockup_key.as_ref(),
+                &[ctx.bumps.reward_vault],
+            ],
+            reward_amount,
+        )?;
+    }
+
+    stake_pda.reward_claimed_so_far = stake_pda.reward_amount;
+    stake_pda.cooldown_end_time = cooldown_end_time;
+    lockup.record_reward_claim(reward_amount)?;
+
+    emit!(UnstakeRequested {
+        staker: stake_pda.staker,
+        lockup: lockup.key(),
+        nonce,
+        reward_amount,
+        cooldown_end_time,
+    });
+
+    Ok(())
+}
+
+fn run_validations(lockup: &Lockup, stake_pda: &UserStakeData, current_time: i64) -> Result<()> {
+    if stake_pda.stake_claimed {
+        return Err(ZbcnStakeError::RewardAlreadyClaimed.into());
+    }
+    require!(
+        !stake_pda.is_unbonding(),
+        ZbcnStakeError::UnstakeAlreadyRequested
+    );
+    // Emergency withdrawal forfeits reward and goes through `unstake_zbcn`.
+    require!(
+        lockup.state == LockupState::Active || lockup.state == LockupState::StakingPaused,
+        ZbcnStakeError::LockupPaused
+    );
+    require!(
+        stake_pda.is_ended_at(current_time)?,
+        ZbcnStakeError::StakeRewardNotClaimable
+    );
+
+    Ok(())
+}
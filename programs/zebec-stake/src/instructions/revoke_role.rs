@@ -0,0 +1,37 @@
+use crate::{error::ZbcnStakeError, events::RoleRevoked, state::LockupRole, Lockup, LOCKUP};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct RevokeRole<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [LOCKUP.as_bytes(), lockup.stake_info.name.as_bytes()],
+        bump,
+        constraint = lockup.to_account_info().data_len() == 8 + Lockup::INIT_SPACE
+            @ ZbcnStakeError::LockupNotMigrated
+    )]
+    pub lockup: Box<Account<'info, Lockup>>,
+}
+
+pub fn handler(ctx: Context<RevokeRole>, role: LockupRole) -> Result<()> {
+    let lockup = &mut ctx.accounts.lockup;
+    let authority = &ctx.accounts.authority;
+
+    require!(
+        lockup.stake_info.creator == authority.key(),
+        ZbcnStakeError::UnAuthorized
+    );
+
+    let previous_holder = lockup.roles.get(role);
+    lockup.roles.set(role, Pubkey::default());
+
+    emit!(RoleRevoked {
+        lockup: lockup.key(),
+        authority: authority.key(),
+        role,
+        previous_holder,
+    });
+
+    Ok(())
+}
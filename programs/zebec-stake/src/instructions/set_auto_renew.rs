@@ -0,0 +1,63 @@
+use crate::{error::ZbcnStakeError, events::AutoRenewUpdated, Lockup, UserStakeData, LOCKUP};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+#[instruction(params: SetAutoRenewParams)]
+pub struct SetAutoRenew<'info> {
+    /// The position's staker or its delegate.
+    pub staker: Signer<'info>,
+    #[account(
+        seeds = [LOCKUP.as_bytes(), lockup.stake_info.name.as_bytes()],
+        bump,
+        constraint = lockup.to_account_info().data_len() == 8 + Lockup::INIT_SPACE
+            @ ZbcnStakeError::LockupNotMigrated
+    )]
+    pub lockup: Box<Account<'info, Lockup>>,
+    #[account(
+        mut,
+        seeds = [stake_pda.staker.as_ref(), lockup.key().as_ref(), &params.nonce.to_le_bytes()],
+        bump,
+        constraint = stake_pda.can_manage(staker.key()) @ ZbcnStakeError::UnAuthorized
+    )]
+    pub stake_pda: Box<Account<'info, UserStakeData>>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct SetAutoRenewParams {
+    pub nonce: u64,
+    pub auto_renew: bool,
+    pub compound_on_renew: bool,
+}
+
+pub fn handler(ctx: Context<SetAutoRenew>, params: SetAutoRenewParams) -> Result<()> {
+    let lockup = &ctx.accounts.lockup;
+    let stake_pda = &mut ctx.accounts.stake_pda;
+
+    require!(
+        !stake_pda.stake_claimed,
+        ZbcnStakeError::StakeAlreadyClaimed
+    );
+    require!(
+        !stake_pda.is_tokenized(),
+        ZbcnStakeError::PositionTokenized
+    );
+    // Compounding pays the reward into the stake vault.
+    require!(
+        !params.compound_on_renew
+            || lockup.reward_token.token_address == lockup.staked_token.token_address,
+        ZbcnStakeError::CompoundNotSupported
+    );
+
+    stake_pda.auto_renew = params.auto_renew;
+    stake_pda.compound_on_renew = params.auto_renew && params.compound_on_renew;
+
+    emit!(AutoRenewUpdated {
+        staker: stake_pda.staker,
+        lockup: lockup.key(),
+        nonce: params.nonce,
+        auto_renew: stake_pda.auto_renew,
+        compound_on_renew: stake_pda.compound_on_renew,
+    });
+
+    Ok(())
+}
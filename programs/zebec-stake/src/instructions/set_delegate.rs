@@ -0,0 +1,47 @@
+use crate::{error::ZbcnStakeError, events::DelegateUpdated, Lockup, UserStakeData, LOCKUP};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+#[instruction(nonce: u64)]
+pub struct SetDelegate<'info> {
+    pub staker: Signer<'info>,
+    #[account(
+        seeds = [LOCKUP.as_bytes(), lockup.stake_info.name.as_bytes()],
+        bump,
+        constraint = lockup.to_account_info().data_len() == 8 + Lockup::INIT_SPACE
+            @ ZbcnStakeError::LockupNotMigrated
+    )]
+    pub lockup: Box<Account<'info, Lockup>>,
+    #[account(
+        mut,
+        seeds = [staker.key().as_ref(), lockup.key().as_ref(), &nonce.to_le_bytes()],
+        bump
+    )]
+    pub stake_pda: Box<Account<'info, UserStakeData>>,
+}
+
+pub fn handler(ctx: Context<SetDelegate>, nonce: u64, delegate: Pubkey) -> Result<()> {
+    let stake_pda = &mut ctx.accounts.stake_pda;
+
+    require!(
+        !stake_pda.stake_claimed,
+        ZbcnStakeError::StakeAlreadyClaimed
+    );
+    // A tokenized position is managed by whoever holds its receipt.
+    require!(!stake_pda.is_tokenized(), ZbcnStakeError::PositionTokenized);
+    require!(
+        delegate != Pubkey::default() && delegate != stake_pda.staker,
+        ZbcnStakeError::InvalidDelegate
+    );
+
+    stake_pda.delegate = Some(delegate);
+
+    emit!(DelegateUpdated {
+        staker: stake_pda.staker,
+        lockup: ctx.accounts.lockup.key(),
+        nonce,
+        delegate: stake_pda.delegate,
+    });
+
+    Ok(())
+}
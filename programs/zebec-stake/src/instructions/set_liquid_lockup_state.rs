@@ -0,0 +1,36 @@
+use crate::{
+    events::LockupStateChanged,
+    state::{LockupRole, LockupState},
+    LiquidLockup, LIQUID_LOCKUP,
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetLiquidLockupState<'info> {
+    pub pauser: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [LIQUID_LOCKUP.as_bytes(), liquid_lockup.name.as_bytes()],
+        bump
+    )]
+    pub liquid_lockup: Box<Account<'info, LiquidLockup>>,
+}
+
+pub fn handler(ctx: Context<SetLiquidLockupState>, state: LockupState) -> Result<()> {
+    let liquid_lockup = &mut ctx.accounts.liquid_lockup;
+    let pauser = &ctx.accounts.pauser;
+
+    liquid_lockup.require_role(LockupRole::Pauser, pauser.key())?;
+
+    let previous_state = liquid_lockup.state;
+    liquid_lockup.state = state;
+
+    emit!(LockupStateChanged {
+        lockup: liquid_lockup.key(),
+        pauser: pauser.key(),
+        previous_state,
+        state,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use crate::{
+    error::ZbcnStakeError,
+    events::LockupOptionsUpdated,
+    state::{LockupOptions, LockupRole},
+    Lockup, LOCKUP, MAX_COOLDOWN_SECONDS,
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetLockupOptions<'info> {
+    pub updater: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [LOCKUP.as_bytes(), lockup.stake_info.name.as_bytes()],
+        bump,
+        constraint = lockup.to_account_info().data_len() == 8 + Lockup::INIT_SPACE
+            @ ZbcnStakeError::LockupNotMigrated
+    )]
+    pub lockup: Box<Account<'info, Lockup>>,
+}
+
+pub fn handler(ctx: Context<SetLockupOptions>, options: LockupOptions) -> Result<()> {
+    let lockup = &mut ctx.accounts.lockup;
+    let updater = &ctx.accounts.updater;
+
+    lockup.require_role(LockupRole::ConfigAdmin, updater.key())?;
+    require!(
+        (0..=MAX_COOLDOWN_SECONDS).contains(&options.cooldown_seconds),
+        ZbcnStakeError::InvalidCooldown
+    );
+
+    lockup.options = options;
+
+    emit!(LockupOptionsUpdated {
+        lockup: lockup.key(),
+        updater: updater.key(),
+        options: lockup.options.clone(),
+    });
+
+    Ok(())
+}
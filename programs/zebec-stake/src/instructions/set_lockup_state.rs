@@ -0,0 +1,39 @@
+use crate::{
+    error::ZbcnStakeError,
+    events::LockupStateChanged,
+    state::{LockupRole, LockupState},
+    Lockup, LOCKUP,
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetLockupState<'info> {
+    pub pauser: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [LOCKUP.as_bytes(), lockup.stake_info.name.as_bytes()],
+        bump,
+        constraint = lockup.to_account_info().data_len() == 8 + Lockup::INIT_SPACE
+            @ ZbcnStakeError::LockupNotMigrated
+    )]
+    pub lockup: Box<Account<'info, Lockup>>,
+}
+
+pub fn handler(ctx: Context<SetLockupState>, state: LockupState) -> Result<()> {
+    let lockup = &mut ctx.accounts.lockup;
+    let pauser = &ctx.accounts.pauser;
+
+    lockup.require_role(LockupRole::Pauser, pauser.key())?;
+
+    let previous_state = lockup.state;
+    lockup.state = state;
+
+    emit!(LockupStateChanged {
+        lockup: lockup.key(),
+        pauser: pauser.key(),
+        previous_state,
+        state,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,52 @@
+use crate::{
+    constants::FEE_DENOMINATOR,
+    error::ZbcnStakeError,
+    events::PenaltyScheduleUpdated,
+    state::{LockupRole, PenaltyDestination, PenaltySchedule},
+    Lockup, LOCKUP,
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetPenaltySchedule<'info> {
+    pub updater: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [LOCKUP.as_bytes(), lockup.stake_info.name.as_bytes()],
+        bump,
+        constraint = lockup.to_account_info().data_len() == 8 + Lockup::INIT_SPACE
+            @ ZbcnStakeError::LockupNotMigrated
+    )]
+    pub lockup: Box<Account<'info, Lockup>>,
+}
+
+pub fn handler(ctx: Context<SetPenaltySchedule>, penalty_schedule: PenaltySchedule) -> Result<()> {
+    let lockup = &mut ctx.accounts.lockup;
+    let updater = &ctx.accounts.updater;
+
+    lockup.require_role(LockupRole::FeeAdmin, updater.key())?;
+    require!(
+        penalty_schedule
+            .penalties
+            .iter()
+            .all(|p| p.penalty <= FEE_DENOMINATOR),
+        ZbcnStakeError::InvalidPenalty
+    );
+    // Penalties are withheld from principal, so they can only be paid into the
+    // reward vault when it holds the same mint.
+    require!(
+        penalty_schedule.destination == PenaltyDestination::FeeVault
+            || lockup.reward_token.token_address == lockup.staked_token.token_address,
+        ZbcnStakeError::InvalidPenaltyDestination
+    );
+
+    lockup.penalty_schedule = penalty_schedule;
+
+    emit!(PenaltyScheduleUpdated {
+        lockup: lockup.key(),
+        updater: updater.key(),
+        penalty_schedule: lockup.penalty_schedule.clone(),
+    });
+
+    Ok(())
+}
@@ -1,11 +1,23 @@
 use crate::{
-    error::ZbcnStakeError, events::Staked, state::UserNonce, Lockup, UserStakeData, LOCKUP,
-    STAKE_VAULT,
+    error::ZbcnStakeError,
+    events::{ReceiptMinted, Staked},
+    state::UserNonce,
+    utils::{calculate_reward, LockPeriod},
+    Lockup, UserStakeData, LOCKUP, RECEIPT_MINT, RECEIPT_SYMBOL, REWARD_VAULT, STAKE_VAULT,
+};
+use anchor_lang::{
+    prelude::*,
+    system_program::{self, Transfer as SystemTransfer},
 };
-use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
     token::{transfer, Mint, Token, TokenAccount, Transfer},
+    token_2022::{
+        mint_to, set_authority, spl_token_2022::instruction::AuthorityType, MintTo, SetAuthority,
+        Token2022,
+    },
+    token_2022_extensions::spl_token_metadata_interface::state::TokenMetadata,
+    token_interface::{self, token_metadata_initialize, TokenMetadataInitialize},
 };
 
 #[derive(Accounts)]
@@ -18,7 +30,9 @@ pub struct Stake<'info> {
     #[account(
         mut,
         seeds =[LOCKUP.as_bytes(), lockup.stake_info.name.as_bytes()],
-        bump
+        bump,
+        constraint = lockup.to_account_info().data_len() == 8 + Lockup::INIT_SPACE
+            @ ZbcnStakeError::LockupNotMigrated
     )]
     pub lockup: Account<'info, Lockup>,
     #[account(
@@ -58,6 +72,51 @@ pub struct Stake<'info> {
         associated_token::authority = stake_vault,
     )]
     pub stake_vault_token_account: Account<'info, TokenAccount>,
+    #[account(
+        constraint = reward_token.key() == lockup.reward_token.token_address
+            @ ZbcnStakeError::InvalidRewardToken
+    )]
+    pub reward_token: Box<Account<'info, Mint>>,
+    #[account(
+        seeds = [REWARD_VAULT.as_bytes(), lockup.key().as_ref()],
+        bump
+    )]
+    /// CHECK: seeds has been checked
+    pub reward_vault: AccountInfo<'info>,
+    /// Created on the first stake into a lockup whose reward vault has not
+    /// been funded yet; the coverage check then rejects any owed reward.
+    #[account(
+        init_if_needed,
+        payer = fee_payer,
+        associated_token::mint = reward_token,
+        associated_token::authority = reward_vault,
+    )]
+    pub reward_vault_token_account: Box<Account<'info, TokenAccount>>,
+    /// When provided, the position is tokenized: a single Token-2022 receipt
+    /// carrying its own metadata is minted to `staker_receipt_account` and its
+    /// holder manages the position. The mint has no freeze authority and its
+    /// mint authority is revoked once the receipt is minted.
+    #[account(
+        init,
+        payer = fee_payer,
+        mint::decimals = 0,
+        mint::authority = lockup,
+        mint::token_program = receipt_token_program,
+        extensions::metadata_pointer::authority = lockup,
+        extensions::metadata_pointer::metadata_address = receipt_mint,
+        seeds = [RECEIPT_MINT.as_bytes(), stake_pda.key().as_ref()],
+        bump
+    )]
+    pub receipt_mint: Option<Box<InterfaceAccount<'info, token_interface::Mint>>>,
+    #[account(
+        init,
+        payer = fee_payer,
+        associated_token::mint = receipt_mint,
+        associated_token::authority = staker,
+        associated_token::token_program = receipt_token_program,
+    )]
+    pub staker_receipt_account: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    pub receipt_token_program: Option<Program<'info, Token2022>>,
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
     pub associated_token_program: Program<'info, AssociatedToken>,
@@ -80,12 +139,15 @@ pub fn handler(ctx: Context<Stake>, params: StakeParams) -> Result<()> {
     let current_time = Clock::get()?.unix_timestamp;
     let stake_token = &ctx.accounts.stake_token;
     let user_nonce = &mut ctx.accounts.user_nonce;
+    let reward_vault_token_account = &ctx.accounts.reward_vault_token_account;
 
     if user_nonce.nonce != params.nonce {
         return Err(ZbcnStakeError::InvaildNonce.into());
     }
 
-    run_validations(stake_token.key(), lockup, params.amount)?;
+    let lock_period = LockPeriod::from_seconds(params.lock_period)?;
+    let reward_rate = run_validations(stake_token.key(), lockup, params.amount, lock_period)?;
+    let reward_amount = calculate_reward(params.amount, reward_rate, lock_period)?;
 
     let trns_spl = Transfer {
         from: staker_stake_token_account.to_account_info(),
@@ -100,11 +162,113 @@ pub fn handler(ctx: Context<Stake>, params: StakeParams) -> Result<()> {
     stake_pda.staked_amount = params.amount;
     stake_pda.created_time = current_time;
     stake_pda.nonce = params.nonce;
-    stake_pda.lock_period = params.lock_period;
+    stake_pda.lock_period = lock_period.as_seconds();
     stake_pda.staker = staker.key();
     stake_pda.lockup = lockup.key();
+    stake_pda.reward_rate = reward_rate;
+    stake_pda.fee = lockup.fee_info.fee;
+    stake_pda.penalty = lockup.penalty_for(lock_period);
+    stake_pda.cooldown_seconds = lockup.options.cooldown_seconds;
+    stake_pda.reward_amount = reward_amount;
+    stake_pda.rent_payer = ctx.accounts.fee_payer.key();
     user_nonce.nonce += 1;
-    lockup.staked_token.total_staked += params.amount;
+    lockup.record_stake(params.amount, reward_amount)?;
+    lockup.ensure_reward_coverage(reward_vault_token_account.amount)?;
+
+    match (
+        &ctx.accounts.receipt_mint,
+        &ctx.accounts.staker_receipt_account,
+        &ctx.accounts.receipt_token_program,
+    ) {
+        (Some(receipt_mint), Some(staker_receipt_account), Some(receipt_token_program)) => {
+            let lockup_seeds: &[&[u8]] = &[
+                LOCKUP.as_bytes(),
+                lockup.stake_info.name.as_bytes(),
+                &[ctx.bumps.lockup],
+            ];
+            let receipt_program = receipt_token_program.to_account_info();
+
+            // Token-2022 grows the mint to hold the metadata but does not fund
+            // the extra rent.
+            let metadata = TokenMetadata {
+                name: format!("{} #{}", lockup.stake_info.name, stake_pda.nonce),
+                symbol: RECEIPT_SYMBOL.to_string(),
+                mint: receipt_mint.key(),
+                update_authority: Some(lockup.key()).try_into()?,
+                ..Default::default()
+            };
+            let receipt_info = receipt_mint.to_account_info();
+            let required_lamports = Rent::get()?
+                .minimum_balance(receipt_info.data_len() + metadata.tlv_size_of()?)
+                .saturating_sub(receipt_info.lamports());
+            if required_lamports > 0 {
+                system_program::transfer(
+                    CpiContext::new(
+                        ctx.accounts.system_program.to_account_info(),
+                        SystemTransfer {
+                            from: ctx.accounts.fee_payer.to_account_info(),
+                            to: receipt_info.clone(),
+                        },
+                    ),
+                    required_lamports,
+                )?;
+            }
+            token_metadata_initialize(
+                CpiContext::new_with_signer(
+                    receipt_program.clone(),
+                    TokenMetadataInitialize {
+                        program_id: receipt_program.clone(),
+                        metadata: receipt_info.clone(),
+                        update_authority: lockup.to_account_info(),
+                        mint_authority: lockup.to_account_info(),
+                        mint: receipt_info.clone(),
+                    },
+                    &[lockup_seeds],
+                ),
+                metadata.name,
+                metadata.symbol,
+                metadata.uri,
+            )?;
+
+            // Mint the single receipt and drop the mint authority so the supply
+            // stays fixed at one.
+            mint_to(
+                CpiContext::new_with_signer(
+                    receipt_program.clone(),
+                    MintTo {
+                        mint: receipt_mint.to_account_info(),
+                        to: staker_receipt_account.to_account_info(),
+                        authority: lockup.to_account_info(),
+                    },
+                    &[lockup_seeds],
+                ),
+                1,
+            )?;
+            set_authority(
+                CpiContext::new_with_signer(
+                    receipt_program,
+                    SetAuthority {
+                        current_authority: lockup.to_account_info(),
+                        account_or_mint: receipt_mint.to_account_info(),
+                    },
+                    &[lockup_seeds],
+                ),
+                AuthorityType::MintTokens,
+                None,
+            )?;
+
+            stake_pda.receipt_mint = Some(receipt_mint.key());
+
+            emit!(ReceiptMinted {
+                staker: staker.key(),
+                lockup: lockup.key(),
+                nonce: stake_pda.nonce,
+                receipt_mint: receipt_mint.key(),
+            });
+        }
+        (None, None, _) => {}
+        _ => return err!(ZbcnStakeError::ReceiptRequired),
+    }
 
     emit!(Staked {
         staker: staker.key(),
@@ -116,7 +280,14 @@ pub fn handler(ctx: Context<Stake>, params: StakeParams) -> Result<()> {
     Ok(())
 }
 
-fn run_validations(stake_token: Pubkey, lockup: &Lockup, amount: u64) -> Result<()> {
+/// Returns the annual reward rate offered for the requested lock period.
+fn run_validations(
+    stake_token: Pubkey,
+    lockup: &Lockup,
+    amount: u64,
+    lock_period: LockPeriod,
+) -> Result<u64> {
+    lockup.require_staking_open()?;
     require!(
         stake_token == lockup.staked_token.token_address,
         ZbcnStakeError::InvalidStakeToken
@@ -125,5 +296,6 @@ fn run_validations(stake_token: Pubkey, lockup: &Lockup, amount: u64) -> Result<
         amount >= lockup.stake_info.minimum_stake,
         ZbcnStakeError::MinimumStakeNotMet
     );
-    Ok(())
+
+    lockup.offered_reward_rate(lock_period)
 }
@@ -0,0 +1,120 @@
+use crate::{
+    error::ZbcnStakeError,
+    events::PositionTransferred,
+    state::{LockupState, UserNonce},
+    Lockup, UserStakeData, LOCKUP,
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+#[instruction(params: TransferPositionParams)]
+pub struct TransferPosition<'info> {
+    pub staker: Signer<'info>,
+    #[account(mut)]
+    pub fee_payer: Signer<'info>,
+    /// CHECK: new owner of the position; only used to derive its accounts
+    pub recipient: AccountInfo<'info>,
+    #[account(
+        seeds = [LOCKUP.as_bytes(), lockup.stake_info.name.as_bytes()],
+        bump,
+        constraint = lockup.to_account_info().data_len() == 8 + Lockup::INIT_SPACE
+            @ ZbcnStakeError::LockupNotMigrated
+    )]
+    pub lockup: Box<Account<'info, Lockup>>,
+    #[account(
+        mut,
+        close = rent_payer,
+        has_one = rent_payer @ ZbcnStakeError::InvalidRentPayer,
+        seeds = [staker.key().as_ref(), lockup.key().as_ref(), &params.nonce.to_le_bytes()],
+        bump
+    )]
+    pub stake_pda: Box<Account<'info, UserStakeData>>,
+    #[account(mut)]
+    /// CHECK: matched against `stake_pda.rent_payer`
+    pub rent_payer: AccountInfo<'info>,
+    #[account(
+        init_if_needed,
+        payer = fee_payer,
+        space = 8 + UserNonce::INIT_SPACE,
+        seeds = [recipient.key().as_ref(), lockup.key().as_ref()],
+        bump
+    )]
+    pub recipient_nonce: Box<Account<'info, UserNonce>>,
+    #[account(
+        init,
+        payer = fee_payer,
+        space = 8 + UserStakeData::INIT_SPACE,
+        seeds = [recipient.key().as_ref(), lockup.key().as_ref(), &params.new_nonce.to_le_bytes()],
+        bump
+    )]
+    pub new_stake_pda: Box<Account<'info, UserStakeData>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct TransferPositionParams {
+    /// Position to transfer.
+    pub nonce: u64,
+    /// Nonce of the position under the recipient.
+    pub new_nonce: u64,
+}
+
+/// Re-homes an open position under `recipient`. Amounts, lock and reward
+/// progress carry over unchanged; the old account is closed to its rent payer
+/// and the fee payer funds the new one.
+pub fn handler(ctx: Context<TransferPosition>, params: TransferPositionParams) -> Result<()> {
+    let lockup = &ctx.accounts.lockup;
+    let stake_pda = &ctx.accounts.stake_pda;
+    let recipient_nonce = &mut ctx.accounts.recipient_nonce;
+    let recipient = ctx.accounts.recipient.key();
+
+    require!(
+        recipient_nonce.nonce == params.new_nonce,
+        ZbcnStakeError::InvaildNonce
+    );
+    run_validations(lockup, stake_pda)?;
+
+    ctx.accounts.new_stake_pda.set_inner(UserStakeData {
+        nonce: params.new_nonce,
+        staker: recipient,
+        rent_payer: ctx.accounts.fee_payer.key(),
+        delegate: None,
+        ..UserStakeData::clone(stake_pda)
+    });
+    recipient_nonce.nonce += 1;
+
+    emit!(PositionTransferred {
+        lockup: lockup.key(),
+        from: stake_pda.staker,
+        to: recipient,
+        nonce: params.nonce,
+        new_nonce: params.new_nonce,
+        staked_amount: stake_pda.staked_amount,
+    });
+
+    Ok(())
+}
+
+fn run_validations(lockup: &Lockup, stake_pda: &UserStakeData) -> Result<()> {
+    require!(
+        lockup.options.transfers_enabled,
+        ZbcnStakeError::TransfersDisabled
+    );
+    require!(
+        lockup.state != LockupState::Paused,
+        ZbcnStakeError::LockupPaused
+    );
+    if stake_pda.stake_claimed {
+        return Err(ZbcnStakeError::StakeAlreadyClaimed.into());
+    }
+    require!(
+        !stake_pda.is_unbonding(),
+        ZbcnStakeError::UnstakeAlreadyRequested
+    );
+    require!(
+        !stake_pda.is_tokenized(),
+        ZbcnStakeError::PositionTokenized
+    );
+
+    Ok(())
+}
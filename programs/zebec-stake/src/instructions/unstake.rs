@@ -1,30 +1,46 @@
 use crate::{
-    constants::REWARD_VAULT, error::ZbcnStakeError, events::Unstaked, Lockup, UserStakeData,
-    LOCKUP, SECONDS_PER_YEAR, STAKE_VAULT,
+    constants::REWARD_VAULT,
+    error::ZbcnStakeError,
+    events::{PositionClosed, ReceiptBurned, Unstaked},
+    utils::{burn_receipt, calculate_fee},
+    Lockup, LockupState, UserStakeData, LOCKUP, STAKE_VAULT,
 };
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
     token::{transfer, Mint, Token, TokenAccount, Transfer},
+    token_2022::Token2022,
+    token_interface,
 };
 
 #[derive(Accounts)]
 #[instruction(_nonce: u64)]
 pub struct Unstake<'info> {
+    /// The position's staker or delegate, or the receipt holder for a
+    /// tokenized position.
     #[account(mut)]
     pub staker: Signer<'info>,
+    /// CHECK: address has been checked; only receives the payout
+    #[account(
+        address = stake_pda.beneficiary(staker.key()) @ ZbcnStakeError::InvalidStaker
+    )]
+    pub beneficiary: AccountInfo<'info>,
     #[account(mut)]
     pub fee_payer: Signer<'info>,
     #[account(
         mut,
         seeds = [LOCKUP.as_bytes(), lockup.stake_info.name.as_bytes()],
-        bump
+        bump,
+        constraint = lockup.to_account_info().data_len() == 8 + Lockup::INIT_SPACE
+            @ ZbcnStakeError::LockupNotMigrated
     )]
     pub lockup: Box<Account<'info, Lockup>>,
     #[account(
         mut,
-        seeds = [staker.key().as_ref(), lockup.key().as_ref(), &_nonce.to_le_bytes()],
-        bump
+        seeds = [stake_pda.staker.as_ref(), lockup.key().as_ref(), &_nonce.to_le_bytes()],
+        bump,
+        constraint = stake_pda.is_tokenized() || stake_pda.can_manage(staker.key())
+            @ ZbcnStakeError::UnAuthorized
     )]
     pub stake_pda: Box<Account<'info, UserStakeData>>,
     pub reward_token: Box<Account<'info, Mint>>,
@@ -33,14 +49,14 @@ pub struct Unstake<'info> {
         init_if_needed,
         payer = fee_payer,
         associated_token::mint = stake_token,
-        associated_token::authority = staker,
+        associated_token::authority = beneficiary,
     )]
     pub staker_token_account: Box<Account<'info, TokenAccount>>,
     #[account(
         init_if_needed,
         payer = fee_payer,
         associated_token::mint = reward_token,
-        associated_token::authority = staker,
+        associated_token::authority = beneficiary,
     )]
     pub staker_reward_token_account: Box<Account<'info, TokenAccount>>,
     #[account(
@@ -82,13 +98,43 @@ pub struct Unstake<'info> {
         associated_token::authority = lockup.fee_info.fee_vault,
     )]
     pub fee_vault_token_account: Box<Account<'info, TokenAccount>>,
+    /// When provided, the claimed position is closed and its rent returned here.
+    #[account(
+        mut,
+        address = stake_pda.rent_payer @ ZbcnStakeError::InvalidRentPayer,
+    )]
+    /// CHECK: address has been checked
+    pub rent_payer: Option<AccountInfo<'info>>,
+    /// Required for a tokenized position; the receipt is burned on unstake.
+    #[account(mut)]
+    pub receipt_mint: Option<Box<InterfaceAccount<'info, token_interface::Mint>>>,
+    #[account(mut)]
+    pub staker_receipt_account: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    pub receipt_token_program: Option<Program<'info, Token2022>>,
     pub token_program: Program<'info, Token>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
 }
 
 pub fn handler(ctx: Context<Unstake>, _nonce: u64) -> Result<()> {
-    let lockup = &ctx.accounts.lockup;
+    // Holding the receipt is what authorizes unstaking a tokenized position.
+    if let Some(receipt_mint) = ctx.accounts.stake_pda.receipt_mint {
+        burn_receipt(
+            receipt_mint,
+            ctx.accounts.receipt_mint.as_deref(),
+            ctx.accounts.staker_receipt_account.as_deref(),
+            ctx.accounts.receipt_token_program.as_ref(),
+            ctx.accounts.staker.to_account_info(),
+        )?;
+        emit!(ReceiptBurned {
+            holder: ctx.accounts.staker.key(),
+            lockup: ctx.accounts.lockup.key(),
+            nonce: ctx.accounts.stake_pda.nonce,
+            receipt_mint,
+        });
+    }
+
+    let lockup = &mut ctx.accounts.lockup;
     let stake_pda = &mut ctx.accounts.stake_pda;
     let stake_vault = &ctx.accounts.stake_vault;
     let reward_vault = &ctx.accounts.reward_vault;
@@ -97,52 +143,60 @@ pub fn handler(ctx: Context<Unstake>, _nonce: u64) -> Result<()> {
     let stake_token = &ctx.accounts.stake_token;
     let fee_vault_token_account = &ctx.accounts.fee_vault_token_account;
     let current_time = Clock::get()?.unix_timestamp;
-    let duration = stake_pda.lock_period;
 
     run_validations(
         reward_token.key(),
         stake_token.key(),
-        &lockup,
-        &stake_pda,
+        lockup,
+        stake_pda,
         current_time,
     )?;
 
-    let annual_reward_rate =
-        lockup.get_reward_for_duration(duration as u64).unwrap() as f64 / 10000.0;
-    let total_reward_amount = stake_pda.staked_amount as f64
-        * (annual_reward_rate / SECONDS_PER_YEAR)
-        * (stake_pda.lock_period as f64);
-
-    if total_reward_amount as u64 == 0 {
-        return Err(ZbcnStakeError::RewardIsZero.into());
-    }
+    // Reward and fee were fixed when the position was opened, so later changes
+    // to the lockup's duration map or fee do not affect this payout. In
+    // emergency-withdraw mode only the principal is returned.
+    let emergency = lockup.state == LockupState::EmergencyWithdraw;
+    let (total_reward_amount, fee_amount) = if emergency {
+        (0, 0)
+    } else {
+        (
+            stake_pda.unclaimed_reward(),
+            calculate_fee(stake_pda.staked_amount, stake_pda.fee)?,
+        )
+    };
 
     let staker_reward_token_account = &ctx.accounts.staker_reward_token_account;
     let reward_vault_token_account = &ctx.accounts.reward_vault_token_account;
 
     let lockup_key = lockup.key();
-    let (_, bump_seed) = Pubkey::find_program_address(
-        &[REWARD_VAULT.as_bytes(), lockup_key.as_ref()],
-        ctx.program_id,
-    );
-    let reward_vault_seed: &[&[&[_]]] =
-        &[&[REWARD_VAULT.as_bytes(), lockup_key.as_ref(), &[bump_seed]]];
-    let trns_spl: Transfer<'_> = Transfer {
-        from: reward_vault_token_account.to_account_info(),
-        to: staker_reward_token_account.to_account_info(),
-        authority: reward_vault.to_account_info(),
-    };
-    let ctx_spl: CpiContext<'_, '_, '_, '_, _> =
-        CpiContext::new_with_signer(token_program.to_account_info(), trns_spl, reward_vault_seed);
-    transfer(ctx_spl, total_reward_amount as u64)?;
+    if total_reward_amount > 0 {
+        let (_, bump_seed) = Pubkey::find_program_address(
+            &[REWARD_VAULT.as_bytes(), lockup_key.as_ref()],
+            ctx.program_id,
+        );
+        let reward_vault_seed: &[&[&[_]]] =
+            &[&[REWARD_VAULT.as_bytes(), lockup_key.as_ref(), &[bump_seed]]];
+        let trns_spl: Transfer<'_> = Transfer {
+            from: reward_vault_token_account.to_account_info(),
+            to: staker_reward_token_account.to_account_info(),
+            authority: reward_vault.to_account_info(),
+        };
+        let ctx_spl: CpiContext<'_, '_, '_, '_, _> = CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            trns_spl,
+            reward_vault_seed,
+        );
+        transfer(ctx_spl, total_reward_amount)?;
+    }
 
-    let fee_amount = (stake_pda.staked_amount * lockup.fee_info.fee) / 1000;
-    let unstake_amount = stake_pda.staked_amount - fee_amount;
+    let unstake_amount = stake_pda
+        .staked_amount
+        .checked_sub(fee_amount)
+        .ok_or(ZbcnStakeError::MathOverflow)?;
 
     let staker_stake_token_account = &ctx.accounts.staker_token_account;
     let stake_vault_token_account = &ctx.accounts.stake_vault_token_account;
 
-    let lockup_key = lockup.key();
     let (_, bump_seed) = Pubkey::find_program_address(
         &[STAKE_VAULT.as_bytes(), lockup_key.as_ref()],
         ctx.program_id,
@@ -176,16 +230,30 @@ pub fn handler(ctx: Context<Unstake>, _nonce: u64) -> Result<()> {
         transfer(ctx_transfer_fee, fee_amount)?;
     }
 
-    stake_pda.reward_amount = total_reward_amount as u64;
+    if emergency {
+        lockup.release_reward_liability(stake_pda.unclaimed_reward())?;
+        stake_pda.reward_amount = stake_pda.reward_claimed_so_far;
+    }
     stake_pda.stake_claimed = true;
+    lockup.record_unstake(stake_pda.staked_amount, total_reward_amount, fee_amount)?;
 
     emit!(Unstaked {
         staker: stake_pda.staker,
-        unstake_amount: unstake_amount,
+        unstake_amount,
         reward_amount: stake_pda.reward_amount,
         lock_period: stake_pda.lock_period,
     });
 
+    if let Some(rent_payer) = &ctx.accounts.rent_payer {
+        emit!(PositionClosed {
+            staker: stake_pda.staker,
+            lockup: lockup_key,
+            nonce: stake_pda.nonce,
+            rent_payer: rent_payer.key(),
+        });
+        stake_pda.close(rent_payer.to_account_info())?;
+    }
+
     Ok(())
 }
 
@@ -200,6 +268,17 @@ fn run_validations(
         return Err(ZbcnStakeError::RewardAlreadyClaimed.into());
     }
 
+    require!(
+        lockup.state != LockupState::Paused,
+        ZbcnStakeError::LockupPaused
+    );
+
+    // Emergency withdrawal skips the cooldown along with the lock period.
+    require!(
+        stake_pda.cooldown_seconds == 0 || lockup.state == LockupState::EmergencyWithdraw,
+        ZbcnStakeError::CooldownRequired
+    );
+
     require!(
         reward_token == lockup.reward_token.token_address,
         ZbcnStakeError::InvalidRewardToken
@@ -211,7 +290,7 @@ fn run_validations(
     );
 
     require!(
-        current_time > (stake_pda.created_time + stake_pda.lock_period),
+        lockup.state == LockupState::EmergencyWithdraw || stake_pda.is_ended_at(current_time)?,
         ZbcnStakeError::StakeRewardNotClaimable
     );
 
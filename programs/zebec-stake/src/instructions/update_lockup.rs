@@ -1,6 +1,8 @@
 use crate::{
-    constants::LOCKUP, Lockup, UpdateLockupParams, events::LockupUpdated,
+    constants::{FEE_DENOMINATOR, LOCKUP},
     error::ZbcnStakeError,
+    events::LockupUpdated,
+    Lockup, UpdateLockupParams,
 };
 use anchor_lang::prelude::*;
 
@@ -12,21 +14,22 @@ pub struct UpdateLockup<'info> {
     #[account(
         mut,
         seeds = [LOCKUP.as_bytes(), lockup.stake_info.name.as_bytes()],
-        bump
+        bump,
+        constraint = lockup.to_account_info().data_len() == 8 + Lockup::INIT_SPACE
+            @ ZbcnStakeError::LockupNotMigrated
     )]
     pub lockup: Box<Account<'info, Lockup>>,
 }
 
 pub fn handler(ctx: Context<UpdateLockup>, params: UpdateLockupParams) -> Result<()> {
     let lockup = &mut ctx.accounts.lockup;
+    let updater = ctx.accounts.updater.key();
 
-    // Validate updater is the lockup creator
-    require!(lockup.stake_info.creator == ctx.accounts.updater.key(), ZbcnStakeError::UnAuthorized);
+    require!(lockup.update_delay == 0, ZbcnStakeError::UpdateTimelocked);
+    require!(params.fee <= FEE_DENOMINATOR, ZbcnStakeError::InvalidFee);
+    lockup.require_update_roles(&params, updater)?;
 
-    lockup.stake_info.minimum_stake = params.minimum_stake;
-    lockup.fee_info.fee = params.fee;
-    lockup.fee_info.fee_vault = params.fee_vault;
-    lockup.stake_info.duration_map = params.duration_map;
+    lockup.update_lockup(params)?;
 
     emit!(LockupUpdated {
         fee: lockup.fee_info.fee,
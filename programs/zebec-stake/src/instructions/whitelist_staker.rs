@@ -1,8 +1,9 @@
 use crate::{
     error::ZbcnStakeError,
     events::StakerWhitelisted,
-    state::{Lockup, UserNonce, UserStakeData},
-    LOCKUP, SECONDS_PER_YEAR,
+    state::{Lockup, LockupRole, UserNonce, UserStakeData},
+    utils::{calculate_reward, LockPeriod},
+    LOCKUP,
 };
 use anchor_lang::prelude::*;
 use anchor_spl::token::Mint;
@@ -15,7 +16,9 @@ pub struct WhitelistStaker<'info> {
     #[account(
         mut,
         seeds = [LOCKUP.as_bytes(), lockup.stake_info.name.as_bytes()],
-        bump
+        bump,
+        constraint = lockup.to_account_info().data_len() == 8 + Lockup::INIT_SPACE
+            @ ZbcnStakeError::LockupNotMigrated
     )]
     pub lockup: Account<'info, Lockup>,
     #[account(
@@ -59,26 +62,27 @@ pub fn handler(ctx: Context<WhitelistStaker>, params: WhitelistStakerParams) ->
 
     run_validations(stake_token.key(), lockup, user_nonce, &params, admin.key())?;
 
+    let lock_period = LockPeriod::from_seconds(params.lock_period)?;
+
     stake_pda.staked_amount = params.amount;
     stake_pda.created_time = params.created_time;
     stake_pda.nonce = params.nonce;
-    stake_pda.lock_period = params.lock_period;
+    stake_pda.lock_period = lock_period.as_seconds();
     stake_pda.stake_claimed = params.claimed;
     stake_pda.staker = staker.key();
     stake_pda.lockup = lockup.key();
+    stake_pda.reward_rate = lockup.offered_reward_rate(lock_period)?;
+    stake_pda.fee = lockup.fee_info.fee;
+    stake_pda.penalty = lockup.penalty_for(lock_period);
+    stake_pda.cooldown_seconds = lockup.options.cooldown_seconds;
+    stake_pda.reward_amount =
+        calculate_reward(stake_pda.staked_amount, stake_pda.reward_rate, lock_period)?;
+    stake_pda.rent_payer = admin.key();
     user_nonce.nonce += 1;
-    lockup.staked_token.total_staked += params.amount;
-
-    if stake_pda.stake_claimed == true {
-        let annual_reward_rate = lockup
-            .get_reward_for_duration(stake_pda.lock_period as u64)
-            .unwrap() as f64
-            / 10000.0;
-        let total_reward_amount = stake_pda.staked_amount as f64
-            * (annual_reward_rate / SECONDS_PER_YEAR)
-            * (stake_pda.lock_period as f64);
 
-        stake_pda.reward_amount = total_reward_amount as u64;
+    // Positions whitelisted as already claimed hold no principal in the vault.
+    if !stake_pda.stake_claimed {
+        lockup.record_stake(params.amount, stake_pda.reward_amount)?;
     }
 
     emit!(StakerWhitelisted {
@@ -105,12 +109,8 @@ fn run_validations(
         ZbcnStakeError::InvalidStakeToken
     );
 
-    require!(
-        lockup.stake_info.creator == admin,
-        ZbcnStakeError::UnAuthorized
-    );
-
-    require!(params.lock_period > 0, ZbcnStakeError::InvalidLockPeriod);
+    lockup.require_role(LockupRole::Operator, admin)?;
+    lockup.require_staking_open()?;
 
     require!(params.amount > 0, ZbcnStakeError::InvalidAmount);
 
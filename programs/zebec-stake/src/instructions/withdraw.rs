@@ -0,0 +1,195 @@
+use crate::{
+    error::ZbcnStakeError,
+    events::{PositionClosed, ReceiptBurned, Withdrawn},
+    state::LockupState,
+    utils::{burn_receipt, calculate_fee, transfer_from_vault},
+    Lockup, UserStakeData, LOCKUP, STAKE_VAULT,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount},
+    token_2022::Token2022,
+    token_interface,
+};
+
+#[derive(Accounts)]
+#[instruction(nonce: u64)]
+pub struct Withdraw<'info> {
+    /// The position's staker or delegate, or the receipt holder for a
+    /// tokenized position.
+    #[account(mut)]
+    pub staker: Signer<'info>,
+    /// CHECK: address has been checked; only receives the payout
+    #[account(
+        address = stake_pda.beneficiary(staker.key()) @ ZbcnStakeError::InvalidStaker
+    )]
+    pub beneficiary: AccountInfo<'info>,
+    #[account(mut)]
+    pub fee_payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [LOCKUP.as_bytes(), lockup.stake_info.name.as_bytes()],
+        bump,
+        constraint = lockup.to_account_info().data_len() == 8 + Lockup::INIT_SPACE
+            @ ZbcnStakeError::LockupNotMigrated
+    )]
+    pub lockup: Box<Account<'info, Lockup>>,
+    #[account(
+        mut,
+        seeds = [stake_pda.staker.as_ref(), lockup.key().as_ref(), &nonce.to_le_bytes()],
+        bump,
+        constraint = stake_pda.is_tokenized() || stake_pda.can_manage(staker.key())
+            @ ZbcnStakeError::UnAuthorized
+    )]
+    pub stake_pda: Box<Account<'info, UserStakeData>>,
+    #[account(
+        constraint = stake_token.key() == lockup.staked_token.token_address
+            @ ZbcnStakeError::InvalidStakeToken
+    )]
+    pub stake_token: Box<Account<'info, Mint>>,
+    #[account(
+        init_if_needed,
+        payer = fee_payer,
+        associated_token::mint = stake_token,
+        associated_token::authority = beneficiary,
+    )]
+    pub staker_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        seeds = [STAKE_VAULT.as_bytes(), lockup.key().as_ref()],
+        bump
+    )]
+    /// CHECK: seeds has been checked
+    pub stake_vault: AccountInfo<'info>,
+    #[account(
+        mut,
+        associated_token::mint = stake_token,
+        associated_token::authority = stake_vault,
+    )]
+    pub stake_vault_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = stake_token,
+        associated_token::authority = lockup.fee_info.fee_vault,
+    )]
+    pub fee_vault_token_account: Box<Account<'info, TokenAccount>>,
+    /// When provided, the claimed position is closed and its rent returned here.
+    #[account(
+        mut,
+        address = stake_pda.rent_payer @ ZbcnStakeError::InvalidRentPayer,
+    )]
+    /// CHECK: address has been checked
+    pub rent_payer: Option<AccountInfo<'info>>,
+    /// Required for a tokenized position; the receipt is burned on withdraw.
+    #[account(mut)]
+    pub receipt_mint: Option<Box<InterfaceAccount<'info, token_interface::Mint>>>,
+    #[account(mut)]
+    pub staker_receipt_account: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    pub receipt_token_program: Option<Program<'info, Token2022>>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Releases the principal of a position whose cooldown has elapsed, less the
+/// unstake fee captured when the lock started.
+pub fn handler(ctx: Context<Withdraw>, nonce: u64) -> Result<()> {
+    if let Some(receipt_mint) = ctx.accounts.stake_pda.receipt_mint {
+        burn_receipt(
+            receipt_mint,
+            ctx.accounts.receipt_mint.as_deref(),
+            ctx.accounts.staker_receipt_account.as_deref(),
+            ctx.accounts.receipt_token_program.as_ref(),
+            ctx.accounts.staker.to_account_info(),
+        )?;
+        emit!(ReceiptBurned {
+            holder: ctx.accounts.staker.key(),
+            lockup: ctx.accounts.lockup.key(),
+            nonce,
+            receipt_mint,
+        });
+    }
+
+    let lockup = &mut ctx.accounts.lockup;
+    let stake_pda = &mut ctx.accounts.stake_pda;
+    let current_time = Clock::get()?.unix_timestamp;
+
+    run_validations(lockup, stake_pda, current_time)?;
+
+    let fee_amount = calculate_fee(stake_pda.staked_amount, stake_pda.fee)?;
+    let unstake_amount = stake_pda
+        .staked_amount
+        .checked_sub(fee_amount)
+        .ok_or(ZbcnStakeError::MathOverflow)?;
+
+    let lockup_key = lockup.key();
+    let token_program = ctx.accounts.token_program.to_account_info();
+    let stake_vault_seeds: &[&[u8]] = &[
+        STAKE_VAULT.as_bytes(),
+        lockup_key.as_ref(),
+        &[ctx.bumps.stake_vault],
+    ];
+
+    transfer_from_vault(
+        token_program.clone(),
+        ctx.accounts.stake_vault_token_account.to_account_info(),
+        ctx.accounts.staker_token_account.to_account_info(),
+        ctx.accounts.stake_vault.to_account_info(),
+        stake_vault_seeds,
+        unstake_amount,
+    )?;
+    if fee_amount > 0 {
+        transfer_from_vault(
+            token_program,
+            ctx.accounts.stake_vault_token_account.to_account_info(),
+            ctx.accounts.fee_vault_token_account.to_account_info(),
+            ctx.accounts.stake_vault.to_account_info(),
+            stake_vault_seeds,
+            fee_amount,
+        )?;
+    }
+
+    stake_pda.stake_claimed = true;
+    // The reward was already recorded as paid by `request_unstake`.
+    lockup.record_unstake(stake_pda.staked_amount, 0, fee_amount)?;
+
+    emit!(Withdrawn {
+        staker: stake_pda.staker,
+        lockup: lockup_key,
+        nonce,
+        unstake_amount,
+        fee_amount,
+    });
+
+    if let Some(rent_payer) = &ctx.accounts.rent_payer {
+        emit!(PositionClosed {
+            staker: stake_pda.staker,
+            lockup: lockup_key,
+            nonce,
+            rent_payer: rent_payer.key(),
+        });
+        stake_pda.close(rent_payer.to_account_info())?;
+    }
+
+    Ok(())
+}
+
+fn run_validations(lockup: &Lockup, stake_pda: &UserStakeData, current_time: i64) -> Result<()> {
+    if stake_pda.stake_claimed {
+        return Err(ZbcnStakeError::StakeAlreadyClaimed.into());
+    }
+    require!(
+        stake_pda.is_unbonding(),
+        ZbcnStakeError::UnstakeNotRequested
+    );
+    require!(
+        lockup.state != LockupState::Paused,
+        ZbcnStakeError::LockupPaused
+    );
+    require!(
+        current_time >= stake_pda.cooldown_end_time,
+        ZbcnStakeError::CooldownNotElapsed
+    );
+
+    Ok(())
+}
@@ -0,0 +1,88 @@
+use crate::{
+    constants::REWARD_VAULT, error::ZbcnStakeError, events::ExcessRewardsWithdrawn,
+    utils::transfer_from_vault, Lockup, LOCKUP,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+#[derive(Accounts)]
+pub struct WithdrawExcessRewards<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [LOCKUP.as_bytes(), lockup.stake_info.name.as_bytes()],
+        bump,
+        constraint = lockup.to_account_info().data_len() == 8 + Lockup::INIT_SPACE
+            @ ZbcnStakeError::LockupNotMigrated
+    )]
+    pub lockup: Box<Account<'info, Lockup>>,
+    #[account(
+        constraint = reward_token.key() == lockup.reward_token.token_address
+            @ ZbcnStakeError::InvalidRewardToken
+    )]
+    pub reward_token: Box<Account<'info, Mint>>,
+    #[account(
+        seeds = [REWARD_VAULT.as_bytes(), lockup.key().as_ref()],
+        bump
+    )]
+    /// CHECK: seeds has been checked
+    pub reward_vault: AccountInfo<'info>,
+    #[account(
+        mut,
+        associated_token::mint = reward_token,
+        associated_token::authority = reward_vault,
+    )]
+    pub reward_vault_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        token::mint = reward_token,
+    )]
+    pub destination_token_account: Box<Account<'info, TokenAccount>>,
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handler(ctx: Context<WithdrawExcessRewards>, amount: u64) -> Result<()> {
+    let lockup = &ctx.accounts.lockup;
+    let authority = &ctx.accounts.authority;
+    let reward_vault_token_account = &mut ctx.accounts.reward_vault_token_account;
+
+    require!(
+        lockup.stake_info.creator == authority.key(),
+        ZbcnStakeError::UnAuthorized
+    );
+    require!(
+        !lockup.legacy_migration_pending,
+        ZbcnStakeError::LegacyMigrationPending
+    );
+    require!(amount > 0, ZbcnStakeError::InvalidAmount);
+
+    let excess = reward_vault_token_account
+        .amount
+        .saturating_sub(lockup.stats.total_reward_liability);
+    require!(amount <= excess, ZbcnStakeError::WithdrawExceedsExcess);
+
+    let lockup_key = lockup.key();
+    transfer_from_vault(
+        ctx.accounts.token_program.to_account_info(),
+        reward_vault_token_account.to_account_info(),
+        ctx.accounts.destination_token_account.to_account_info(),
+        ctx.accounts.reward_vault.to_account_info(),
+        &[
+            REWARD_VAULT.as_bytes(),
+            lockup_key.as_ref(),
+            &[ctx.bumps.reward_vault],
+        ],
+        amount,
+    )?;
+    reward_vault_token_account.reload()?;
+
+    emit!(ExcessRewardsWithdrawn {
+        lockup: lockup_key,
+        authority: authority.key(),
+        destination: ctx.accounts.destination_token_account.key(),
+        amount,
+        reward_vault_balance: reward_vault_token_account.amount,
+        reward_liability: lockup.stats.total_reward_liability,
+    });
+
+    Ok(())
+}
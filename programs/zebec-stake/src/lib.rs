@@ -1,8 +1,14 @@
+// `#[program]` emits the IDL resize instruction, which still calls the deprecated
+// `AccountInfo::realloc`, into a crate-root `__private` module that no outer
+// attribute can reach. Nothing else in the program relies on this allow.
+#![cfg_attr(not(feature = "no-idl"), allow(deprecated))]
+
 mod constants;
 mod error;
 mod instructions;
 mod state;
 mod events;
+mod utils;
 
 use anchor_lang::prelude::*;
 use constants::*;
@@ -40,4 +46,172 @@ pub mod zebec_stake {
     ) -> Result<()> {
         update_lockup::handler(ctx, params)
     }
+
+    pub fn fund_rewards(ctx: Context<FundRewards>, amount: u64) -> Result<()> {
+        fund_rewards::handler(ctx, amount)
+    }
+
+    pub fn withdraw_excess_rewards(
+        ctx: Context<WithdrawExcessRewards>,
+        amount: u64,
+    ) -> Result<()> {
+        withdraw_excess_rewards::handler(ctx, amount)
+    }
+
+    pub fn propose_authority(ctx: Context<ProposeAuthority>, new_authority: Pubkey) -> Result<()> {
+        propose_authority::handler(ctx, new_authority)
+    }
+
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        accept_authority::handler(ctx)
+    }
+
+    pub fn grant_role(ctx: Context<GrantRole>, role: LockupRole, holder: Pubkey) -> Result<()> {
+        grant_role::handler(ctx, role, holder)
+    }
+
+    pub fn revoke_role(ctx: Context<RevokeRole>, role: LockupRole) -> Result<()> {
+        revoke_role::handler(ctx, role)
+    }
+
+    pub fn set_lockup_state(ctx: Context<SetLockupState>, state: LockupState) -> Result<()> {
+        set_lockup_state::handler(ctx, state)
+    }
+
+    pub fn queue_lockup_update(
+        ctx: Context<QueueLockupUpdate>,
+        params: UpdateLockupParams,
+        update_delay: Option<i64>,
+    ) -> Result<()> {
+        queue_lockup_update::handler(ctx, params, update_delay)
+    }
+
+    pub fn execute_lockup_update(ctx: Context<ExecuteLockupUpdate>) -> Result<()> {
+        execute_lockup_update::handler(ctx)
+    }
+
+    pub fn cancel_lockup_update(ctx: Context<CancelLockupUpdate>) -> Result<()> {
+        cancel_lockup_update::handler(ctx)
+    }
+
+    pub fn partial_unstake(
+        ctx: Context<PartialUnstake>,
+        params: PartialUnstakeParams,
+    ) -> Result<()> {
+        partial_unstake::handler(ctx, params)
+    }
+
+    pub fn set_lockup_options(
+        ctx: Context<SetLockupOptions>,
+        options: LockupOptions,
+    ) -> Result<()> {
+        set_lockup_options::handler(ctx, options)
+    }
+
+    pub fn increase_stake(ctx: Context<IncreaseStake>, params: IncreaseStakeParams) -> Result<()> {
+        increase_stake::handler(ctx, params)
+    }
+
+    pub fn extend_lock(ctx: Context<ExtendLock>, params: ExtendLockParams) -> Result<()> {
+        extend_lock::handler(ctx, params)
+    }
+
+    pub fn merge_positions<'info>(
+        ctx: Context<'_, '_, 'info, 'info, MergePositions<'info>>,
+        nonce: u64,
+    ) -> Result<()> {
+        merge_positions::handler(ctx, nonce)
+    }
+
+    pub fn close_position(ctx: Context<ClosePosition>, nonce: u64) -> Result<()> {
+        close_position::handler(ctx, nonce)
+    }
+
+    pub fn set_penalty_schedule(
+        ctx: Context<SetPenaltySchedule>,
+        penalty_schedule: PenaltySchedule,
+    ) -> Result<()> {
+        set_penalty_schedule::handler(ctx, penalty_schedule)
+    }
+
+    pub fn early_unstake(ctx: Context<EarlyUnstake>, nonce: u64) -> Result<()> {
+        early_unstake::handler(ctx, nonce)
+    }
+
+    pub fn set_auto_renew(ctx: Context<SetAutoRenew>, params: SetAutoRenewParams) -> Result<()> {
+        set_auto_renew::handler(ctx, params)
+    }
+
+    pub fn crank_renew(ctx: Context<CrankRenew>, nonce: u64) -> Result<()> {
+        crank_renew::handler(ctx, nonce)
+    }
+
+    pub fn compound(ctx: Context<Compound>, params: CompoundParams) -> Result<()> {
+        compound::handler(ctx, params)
+    }
+
+    pub fn claim_rewards(ctx: Context<ClaimRewards>, nonce: u64) -> Result<()> {
+        claim_rewards::handler(ctx, nonce)
+    }
+
+    pub fn request_unstake(ctx: Context<RequestUnstake>, nonce: u64) -> Result<()> {
+        request_unstake::handler(ctx, nonce)
+    }
+
+    pub fn withdraw(ctx: Context<Withdraw>, nonce: u64) -> Result<()> {
+        withdraw::handler(ctx, nonce)
+    }
+
+    pub fn transfer_position(
+        ctx: Context<TransferPosition>,
+        params: TransferPositionParams,
+    ) -> Result<()> {
+        transfer_position::handler(ctx, params)
+    }
+
+    pub fn init_liquid_lockup(
+        ctx: Context<InitLiquidLockup>,
+        params: InitLiquidLockupParams,
+    ) -> Result<()> {
+        init_liquid_lockup::handler(ctx, params)
+    }
+
+    pub fn liquid_stake(ctx: Context<LiquidStake>, amount: u64) -> Result<()> {
+        liquid_stake::handler(ctx, amount)
+    }
+
+    pub fn liquid_unstake(ctx: Context<LiquidUnstake>, receipt_amount: u64) -> Result<()> {
+        liquid_unstake::handler(ctx, receipt_amount)
+    }
+
+    pub fn fund_liquid_rewards(ctx: Context<FundLiquidRewards>, amount: u64) -> Result<()> {
+        fund_liquid_rewards::handler(ctx, amount)
+    }
+
+    pub fn set_liquid_lockup_state(
+        ctx: Context<SetLiquidLockupState>,
+        state: LockupState,
+    ) -> Result<()> {
+        set_liquid_lockup_state::handler(ctx, state)
+    }
+
+    pub fn set_delegate(ctx: Context<SetDelegate>, nonce: u64, delegate: Pubkey) -> Result<()> {
+        set_delegate::handler(ctx, nonce, delegate)
+    }
+
+    pub fn revoke_delegate(ctx: Context<RevokeDelegate>, nonce: u64) -> Result<()> {
+        revoke_delegate::handler(ctx, nonce)
+    }
+
+    pub fn complete_legacy_migration(ctx: Context<CompleteLegacyMigration>) -> Result<()> {
+        complete_legacy_migration::handler(ctx)
+    }
+
+    pub fn migrate_lockup(ctx: Context<MigrateLockup>) -> Result<()> {
+        migrate_lockup::handler(ctx)
+    }
+
+    pub fn migrate_stake(ctx: Context<MigrateStake>, nonce: u64) -> Result<()> {
+        migrate_stake::handler(ctx, nonce)
+    }
 }
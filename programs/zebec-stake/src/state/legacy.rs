@@ -0,0 +1,27 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{FeeInfo, RewardToken, StakeInfo, StakedToken};
+
+/// `Lockup` as laid out before the lockup statistics were added. Only read by
+/// `migrate_lockup`.
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace)]
+pub struct LegacyLockup {
+    pub stake_info: StakeInfo,
+    pub reward_token: RewardToken,
+    pub staked_token: StakedToken,
+    pub fee_info: FeeInfo,
+}
+
+/// `UserStakeData` as laid out before the reward rate and fee were
+/// snapshotted into each position. Only read by `migrate_stake`.
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace)]
+pub struct LegacyUserStakeData {
+    pub nonce: u64,
+    pub created_time: i64,
+    pub staked_amount: u64,
+    pub reward_amount: u64,
+    pub stake_claimed: bool,
+    pub lock_period: i64,
+    pub staker: Pubkey,
+    pub lockup: Pubkey,
+}
@@ -0,0 +1,161 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::ZbcnStakeError,
+    state::{LockupRole, LockupRoles, LockupState},
+    utils::{mul_div, Rounding},
+};
+
+/// Lockup variant without per-nonce positions. Stakers hold a fungible receipt
+/// minted against the pool; rewards funded into the pool raise the amount of
+/// staked token each receipt redeems for.
+#[account]
+#[derive(Default, InitSpace)]
+pub struct LiquidLockup {
+    #[max_len(30)]
+    pub name: String,
+    pub creator: Pubkey,
+    /// Token staked into the pool and paid out as reward.
+    pub staked_token: Pubkey,
+    /// Receipt issued on stake and burned on unstake; its mint authority is
+    /// this account.
+    pub receipt_mint: Pubkey,
+    pub minimum_stake: u64,
+    /// Principal held in the stake vault.
+    pub total_staked: u64,
+    /// Funded reward held in the reward vault, owed to receipt holders.
+    pub total_rewards: u64,
+    /// Every role starts with the creator; only the pauser is used so far.
+    pub roles: LockupRoles,
+    /// Any state but active blocks staking; paused blocks unstaking as well.
+    pub state: LockupState,
+}
+
+impl LiquidLockup {
+    pub fn require_role(&self, role: LockupRole, signer: Pubkey) -> Result<()> {
+        let holder = self.roles.get(role);
+        require!(
+            holder != Pubkey::default() && holder == signer,
+            ZbcnStakeError::UnAuthorized
+        );
+        Ok(())
+    }
+
+    pub fn require_staking_open(&self) -> Result<()> {
+        require!(
+            self.state == LockupState::Active,
+            ZbcnStakeError::StakingPaused
+        );
+        Ok(())
+    }
+
+    /// Staked token backing the outstanding receipts.
+    pub fn total_assets(&self) -> Result<u64> {
+        self.total_staked
+            .checked_add(self.total_rewards)
+            .ok_or(ZbcnStakeError::MathOverflow.into())
+    }
+
+    /// Receipts issued for depositing `amount` at the current exchange rate.
+    /// Rounds down so existing holders are never diluted.
+    pub fn receipts_for_deposit(&self, amount: u64, receipt_supply: u64) -> Result<u64> {
+        let total_assets = self.total_assets()?;
+        if receipt_supply == 0 || total_assets == 0 {
+            return Ok(amount);
+        }
+
+        mul_div(
+            amount,
+            receipt_supply as u128,
+            total_assets as u128,
+            Rounding::Down,
+        )
+    }
+
+    /// Principal and reward paid for redeeming `receipts`, each its pro-rata
+    /// share of the pool rounded down.
+    pub fn redemption_for(&self, receipts: u64, receipt_supply: u64) -> Result<(u64, u64)> {
+        require!(
+            receipts > 0 && receipts <= receipt_supply,
+            ZbcnStakeError::InvalidAmount
+        );
+
+        let principal = mul_div(
+            self.total_staked,
+            receipts as u128,
+            receipt_supply as u128,
+            Rounding::Down,
+        )?;
+        let reward = mul_div(
+            self.total_rewards,
+            receipts as u128,
+            receipt_supply as u128,
+            Rounding::Down,
+        )?;
+
+        Ok((principal, reward))
+    }
+
+    pub fn record_deposit(&mut self, amount: u64) -> Result<()> {
+        self.total_staked = self
+            .total_staked
+            .checked_add(amount)
+            .ok_or(ZbcnStakeError::MathOverflow)?;
+        Ok(())
+    }
+
+    pub fn record_reward(&mut self, amount: u64) -> Result<()> {
+        self.total_rewards = self
+            .total_rewards
+            .checked_add(amount)
+            .ok_or(ZbcnStakeError::MathOverflow)?;
+        Ok(())
+    }
+
+    pub fn record_redemption(&mut self, principal: u64, reward: u64) -> Result<()> {
+        self.total_staked = self
+            .total_staked
+            .checked_sub(principal)
+            .ok_or(ZbcnStakeError::MathOverflow)?;
+        self.total_rewards = self
+            .total_rewards
+            .checked_sub(reward)
+            .ok_or(ZbcnStakeError::MathOverflow)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool(total_staked: u64, total_rewards: u64) -> LiquidLockup {
+        LiquidLockup {
+            total_staked,
+            total_rewards,
+            ..LiquidLockup::default()
+        }
+    }
+
+    #[test]
+    fn first_deposit_mints_one_to_one() {
+        assert_eq!(pool(0, 0).receipts_for_deposit(1_000, 0).unwrap(), 1_000);
+    }
+
+    #[test]
+    fn rewards_raise_the_exchange_rate() {
+        // 1000 staked plus 250 reward backs 1000 receipts: 1.25 per receipt.
+        let pool = pool(1_000, 250);
+        assert_eq!(pool.receipts_for_deposit(500, 1_000).unwrap(), 400);
+        assert_eq!(pool.receipts_for_deposit(1, 1_000).unwrap(), 0);
+        assert_eq!(pool.redemption_for(400, 1_000).unwrap(), (400, 100));
+        assert_eq!(pool.redemption_for(1_000, 1_000).unwrap(), (1_000, 250));
+    }
+
+    #[test]
+    fn redemption_rejects_zero_and_excess_receipts() {
+        let pool = pool(1_000, 0);
+        assert!(pool.redemption_for(0, 1_000).is_err());
+        assert!(pool.redemption_for(1_001, 1_000).is_err());
+    }
+}
@@ -1,5 +1,7 @@
 use anchor_lang::prelude::*;
 
+use crate::{error::ZbcnStakeError, utils::LockPeriod};
+
 #[account]
 #[derive(Default, InitSpace)]
 pub struct Lockup {
@@ -7,12 +9,28 @@ pub struct Lockup {
     pub reward_token: RewardToken,
     pub staked_token: StakedToken,
     pub fee_info: FeeInfo,
+    pub stats: LockupStats,
+    /// Key nominated by `propose_authority`, awaiting `accept_authority`.
+    pub pending_authority: Option<Pubkey>,
+    pub roles: LockupRoles,
+    pub state: LockupState,
+    /// Seconds a queued lockup update must wait before it can be executed.
+    /// While non-zero, `update_lockup` is disabled in favour of the queue.
+    pub update_delay: i64,
+    pub options: LockupOptions,
+    pub penalty_schedule: PenaltySchedule,
+    /// Set by `migrate_lockup` because legacy positions owe reward that is not
+    /// yet in `total_reward_liability`. Blocks `withdraw_excess_rewards` until
+    /// `complete_legacy_migration` is called.
+    pub legacy_migration_pending: bool,
 }
 
 #[derive(Default, AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
 pub struct StakeInfo {
     #[max_len(30)]
     pub name: String,
+    /// Lockup authority. Set to the creator at `init_lockup` and moved with
+    /// `propose_authority` / `accept_authority`.
     pub creator: Pubkey,
     pub minimum_stake: u64,
     #[max_len(250)]
@@ -42,6 +60,100 @@ pub struct FeeInfo {
     pub fee_vault: Pubkey,
 }
 
+#[derive(Default, AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct LockupStats {
+    pub total_rewards_paid: u64,
+    pub total_fees_collected: u64,
+    pub active_positions: u64,
+    /// Reward owed to open positions and not yet paid out.
+    pub total_reward_liability: u64,
+    pub total_penalties_collected: u64,
+}
+
+/// Keys allowed to perform each class of administrative action. A revoked
+/// role is held by `Pubkey::default()`, which nobody can sign for.
+#[derive(Default, AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct LockupRoles {
+    /// Changes `minimum_stake` and `duration_map`.
+    pub config_admin: Pubkey,
+    /// Changes `fee` and `fee_vault`.
+    pub fee_admin: Pubkey,
+    /// Whitelists stakers and migrates legacy positions.
+    pub operator: Pubkey,
+    /// Pauses and resumes the lockup.
+    pub pauser: Pubkey,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LockupRole {
+    ConfigAdmin,
+    FeeAdmin,
+    Operator,
+    Pauser,
+}
+
+#[derive(Default, AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum LockupState {
+    #[default]
+    Active,
+    /// New stakes and whitelisting are rejected; unstaking works as usual.
+    StakingPaused,
+    /// Every staking and unstaking instruction is rejected.
+    Paused,
+    /// New stakes are rejected and open positions may withdraw their principal
+    /// at any time, forfeiting reward and paying no fee.
+    EmergencyWithdraw,
+}
+
+/// Behaviour switches set by the config admin with `set_lockup_options`. They
+/// apply immediately and are not subject to `update_delay`.
+#[derive(Default, AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct LockupOptions {
+    pub top_up_policy: TopUpPolicy,
+    /// Lets stakers collect accrued reward with `claim_rewards` before unstaking.
+    pub claim_enabled: bool,
+    /// Seconds between `request_unstake` and `withdraw`. While non-zero,
+    /// matured positions leave through that flow instead of `unstake_zbcn`.
+    pub cooldown_seconds: i64,
+    /// Lets stakers move positions to another wallet with `transfer_position`.
+    pub transfers_enabled: bool,
+}
+
+/// How `increase_stake` moves the lock start of a topped-up position.
+#[derive(Default, AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum TopUpPolicy {
+    /// The whole position restarts its lock at the time of the top-up.
+    #[default]
+    ResetLock,
+    /// The lock start moves to the principal-weighted average of the existing
+    /// start and the time of the top-up.
+    WeightedLock,
+}
+
+/// Penalties charged by `early_unstake`, in tenths of a percent of principal.
+/// Each position captures the penalty for its duration when it is opened.
+#[derive(Default, AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct PenaltySchedule {
+    pub destination: PenaltyDestination,
+    /// Durations without an entry carry no penalty.
+    #[max_len(250)]
+    pub penalties: Vec<DurationPenalty>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct DurationPenalty {
+    pub duration: u64,
+    pub penalty: u64,
+}
+
+#[derive(Default, AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum PenaltyDestination {
+    #[default]
+    FeeVault,
+    /// Only available when the reward and staked tokens are the same mint.
+    RewardVault,
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize)]
 pub struct InitConfigParams {
     pub name: String,
@@ -51,14 +163,58 @@ pub struct InitConfigParams {
     pub duration_map: Vec<DurationMap>,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
 pub struct UpdateLockupParams {
     pub fee: u64,
     pub fee_vault: Pubkey,
     pub minimum_stake: u64,
+    #[max_len(250)]
     pub duration_map: Vec<DurationMap>,
 }
 
+impl LockupRoles {
+    pub fn new(holder: Pubkey) -> Self {
+        Self {
+            config_admin: holder,
+            fee_admin: holder,
+            operator: holder,
+            pauser: holder,
+        }
+    }
+
+    pub fn get(&self, role: LockupRole) -> Pubkey {
+        match role {
+            LockupRole::ConfigAdmin => self.config_admin,
+            LockupRole::FeeAdmin => self.fee_admin,
+            LockupRole::Operator => self.operator,
+            LockupRole::Pauser => self.pauser,
+        }
+    }
+
+    pub fn set(&mut self, role: LockupRole, holder: Pubkey) {
+        match role {
+            LockupRole::ConfigAdmin => self.config_admin = holder,
+            LockupRole::FeeAdmin => self.fee_admin = holder,
+            LockupRole::Operator => self.operator = holder,
+            LockupRole::Pauser => self.pauser = holder,
+        }
+    }
+
+    /// Hands every role held by `from` over to `to`.
+    pub fn reassign(&mut self, from: Pubkey, to: Pubkey) {
+        for role in [
+            LockupRole::ConfigAdmin,
+            LockupRole::FeeAdmin,
+            LockupRole::Operator,
+            LockupRole::Pauser,
+        ] {
+            if self.get(role) == from {
+                self.set(role, to);
+            }
+        }
+    }
+}
+
 impl DurationMap {
     fn new(duration: u64, reward: u64) -> Self {
         Self { duration, reward }
@@ -75,6 +231,7 @@ impl Lockup {
         *self = Self::default();
         self.stake_info.name = params.name;
         self.stake_info.creator = creator;
+        self.roles = LockupRoles::new(creator);
 
         self.reward_token.token_address = reward_token;
 
@@ -110,8 +267,209 @@ impl Lockup {
                 return Some(f.reward);
             }
         }
-        // None
-        Some(0)
+        None
+    }
+
+    /// Annual reward rate for `lock_period`, failing if the lockup does not offer it.
+    pub fn offered_reward_rate(&self, lock_period: LockPeriod) -> Result<u64> {
+        self.get_reward_for_duration(lock_period.duration_key())
+            .ok_or(ZbcnStakeError::LockPeriodNotOffered.into())
+    }
+
+    /// Early-unstake penalty for `lock_period`, zero when none is configured.
+    pub fn penalty_for(&self, lock_period: LockPeriod) -> u64 {
+        self.penalty_schedule
+            .penalties
+            .iter()
+            .find(|p| p.duration == lock_period.duration_key())
+            .map_or(0, |p| p.penalty)
+    }
+
+    pub fn has_role(&self, role: LockupRole, signer: Pubkey) -> bool {
+        let holder = self.roles.get(role);
+        holder != Pubkey::default() && holder == signer
+    }
+
+    pub fn require_role(&self, role: LockupRole, signer: Pubkey) -> Result<()> {
+        require!(self.has_role(role, signer), ZbcnStakeError::UnAuthorized);
+        Ok(())
+    }
+
+    pub fn require_staking_open(&self) -> Result<()> {
+        require!(
+            self.state == LockupState::Active,
+            ZbcnStakeError::StakingPaused
+        );
+        Ok(())
+    }
+
+    /// Records a newly opened position holding `amount` of principal and owed
+    /// `reward` at maturity.
+    pub fn record_stake(&mut self, amount: u64, reward: u64) -> Result<()> {
+        self.staked_token.total_staked = self
+            .staked_token
+            .total_staked
+            .checked_add(amount)
+            .ok_or(ZbcnStakeError::MathOverflow)?;
+        self.stats.active_positions = self
+            .stats
+            .active_positions
+            .checked_add(1)
+            .ok_or(ZbcnStakeError::MathOverflow)?;
+        self.add_reward_liability(reward)
+    }
+
+    /// Records `amount` of principal added to an open position whose owed
+    /// reward changes from `previous_reward` to `reward`.
+    pub fn record_top_up(&mut self, amount: u64, previous_reward: u64, reward: u64) -> Result<()> {
+        self.staked_token.total_staked = self
+            .staked_token
+            .total_staked
+            .checked_add(amount)
+            .ok_or(ZbcnStakeError::MathOverflow)?;
+        self.replace_reward_liability(previous_reward, reward)
+    }
+
+    /// Records a position closed before maturity: `amount` of principal
+    /// released, of which `penalty` was withheld, and its owed `reward` forfeited.
+    pub fn record_early_unstake(&mut self, amount: u64, reward: u64, penalty: u64) -> Result<()> {
+        self.release_reward_liability(reward)?;
+        self.record_unstake(amount, 0, 0)?;
+        self.stats.total_penalties_collected = self
+            .stats
+            .total_penalties_collected
+            .checked_add(penalty)
+            .ok_or(ZbcnStakeError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Records an open position rolled into a new lock owed `reward`, after its
+    /// matured `previous_reward` was paid out or, if `compounded`, added to
+    /// its principal.
+    pub fn record_renewal(&mut self, previous_reward: u64, compounded: bool, reward: u64) -> Result<()> {
+        self.stats.total_rewards_paid = self
+            .stats
+            .total_rewards_paid
+            .checked_add(previous_reward)
+            .ok_or(ZbcnStakeError::MathOverflow)?;
+        if compounded {
+            self.staked_token.total_staked = self
+                .staked_token
+                .total_staked
+                .checked_add(previous_reward)
+                .ok_or(ZbcnStakeError::MathOverflow)?;
+        }
+        self.replace_reward_liability(previous_reward, reward)
+    }
+
+    /// Records `reward` paid to an open position before it is unstaked.
+    pub fn record_reward_claim(&mut self, reward: u64) -> Result<()> {
+        self.stats.total_rewards_paid = self
+            .stats
+            .total_rewards_paid
+            .checked_add(reward)
+            .ok_or(ZbcnStakeError::MathOverflow)?;
+        self.release_reward_liability(reward)
+    }
+
+    /// Records `count` open positions folded into another open position.
+    pub fn record_merge(&mut self, count: u64) -> Result<()> {
+        self.stats.active_positions = self
+            .stats
+            .active_positions
+            .checked_sub(count)
+            .ok_or(ZbcnStakeError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Swaps the liability of an open position owed `previous_reward` for `reward`.
+    pub fn replace_reward_liability(&mut self, previous_reward: u64, reward: u64) -> Result<()> {
+        self.release_reward_liability(previous_reward)?;
+        self.add_reward_liability(reward)
+    }
+
+    /// Records a position leaving the lockup: `amount` of principal released,
+    /// of which `fee` went to the fee vault, plus `reward` paid from the reward vault.
+    pub fn record_unstake(&mut self, amount: u64, reward: u64, fee: u64) -> Result<()> {
+        self.stats.active_positions = self
+            .stats
+            .active_positions
+            .checked_sub(1)
+            .ok_or(ZbcnStakeError::MathOverflow)?;
+        self.record_withdrawal(amount, reward, fee)
+    }
+
+    /// Records `amount` of principal, `reward` and `fee` paid out of a position
+    /// that stays open.
+    pub fn record_withdrawal(&mut self, amount: u64, reward: u64, fee: u64) -> Result<()> {
+        self.staked_token.total_staked = self
+            .staked_token
+            .total_staked
+            .checked_sub(amount)
+            .ok_or(ZbcnStakeError::MathOverflow)?;
+        self.stats.total_rewards_paid = self
+            .stats
+            .total_rewards_paid
+            .checked_add(reward)
+            .ok_or(ZbcnStakeError::MathOverflow)?;
+        self.release_reward_liability(reward)?;
+        self.stats.total_fees_collected = self
+            .stats
+            .total_fees_collected
+            .checked_add(fee)
+            .ok_or(ZbcnStakeError::MathOverflow)?;
+        Ok(())
+    }
+
+    pub fn add_reward_liability(&mut self, reward: u64) -> Result<()> {
+        self.stats.total_reward_liability = self
+            .stats
+            .total_reward_liability
+            .checked_add(reward)
+            .ok_or(ZbcnStakeError::MathOverflow)?;
+        Ok(())
+    }
+
+    pub fn release_reward_liability(&mut self, reward: u64) -> Result<()> {
+        self.stats.total_reward_liability = self
+            .stats
+            .total_reward_liability
+            .checked_sub(reward)
+            .ok_or(ZbcnStakeError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Fails unless the reward vault holds enough to pay every open position.
+    pub fn ensure_reward_coverage(&self, reward_vault_balance: u64) -> Result<()> {
+        require!(
+            self.stats.total_reward_liability <= reward_vault_balance,
+            ZbcnStakeError::InsufficientRewardVault
+        );
+        Ok(())
+    }
+
+    /// Checks that `signer` may apply `params`: it must hold the config admin or
+    /// fee admin role, the fee admin when the fee settings change and the
+    /// config admin when anything else does.
+    pub fn require_update_roles(&self, params: &UpdateLockupParams, signer: Pubkey) -> Result<()> {
+        require!(
+            self.has_role(LockupRole::ConfigAdmin, signer)
+                || self.has_role(LockupRole::FeeAdmin, signer),
+            ZbcnStakeError::UnAuthorized
+        );
+        if params.fee != self.fee_info.fee || params.fee_vault != self.fee_info.fee_vault {
+            self.require_role(LockupRole::FeeAdmin, signer)?;
+        }
+        let duration_map_changed = params.duration_map.len() != self.stake_info.duration_map.len()
+            || params
+                .duration_map
+                .iter()
+                .zip(self.stake_info.duration_map.iter())
+                .any(|(new, old)| new.duration != old.duration || new.reward != old.reward);
+        if params.minimum_stake != self.stake_info.minimum_stake || duration_map_changed {
+            self.require_role(LockupRole::ConfigAdmin, signer)?;
+        }
+        Ok(())
     }
 
     pub fn update_lockup(
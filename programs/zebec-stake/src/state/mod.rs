@@ -1,5 +1,11 @@
+pub mod legacy;
+pub mod liquid_lockup;
 pub mod lockup;
+pub mod pending_update;
 pub mod user_stake;
 
+pub use legacy::*;
+pub use liquid_lockup::*;
 pub use lockup::*;
+pub use pending_update::*;
 pub use user_stake::*;
@@ -0,0 +1,18 @@
+use anchor_lang::prelude::*;
+
+use crate::state::UpdateLockupParams;
+
+/// Lockup update waiting out `Lockup::update_delay`. At most one exists per
+/// lockup; it is closed when executed or cancelled.
+#[account]
+#[derive(InitSpace)]
+pub struct PendingLockupUpdate {
+    pub lockup: Pubkey,
+    pub params: UpdateLockupParams,
+    /// New `Lockup::update_delay`, applied together with `params`.
+    pub update_delay: Option<i64>,
+    pub queued_time: i64,
+    pub effective_time: i64,
+    /// Account that paid the rent and receives it back on close.
+    pub payer: Pubkey,
+}
@@ -1,5 +1,7 @@
 use anchor_lang::prelude::*;
 
+use crate::utils::{mul_div, LockPeriod, Rounding};
+
 #[account]
 #[derive(Default, InitSpace)]
 pub struct UserStakeData {
@@ -8,9 +10,44 @@ pub struct UserStakeData {
     pub staked_amount: u64,
     pub reward_amount: u64,
     pub stake_claimed: bool,
+    /// Lock length in seconds; read through `UserStakeData::lock_period()`.
     pub lock_period: i64,
     pub staker: Pubkey,
     pub lockup: Pubkey,
+    /// Annual reward rate in basis points, fixed when the position is opened.
+    pub reward_rate: u64,
+    /// Unstake fee in tenths of a percent, fixed when the position is opened.
+    pub fee: u64,
+    /// Early-unstake penalty in tenths of a percent, fixed when the lock starts.
+    pub penalty: u64,
+    /// Account that paid the rent and receives it back when the position is closed.
+    pub rent_payer: Pubkey,
+    /// Lets anyone roll the matured position into a new lock of the same
+    /// duration with `crank_renew`.
+    pub auto_renew: bool,
+    /// Adds the matured reward to the principal on renewal instead of paying it out.
+    pub compound_on_renew: bool,
+    /// Part of `reward_amount` already paid by `claim_rewards`. Reset when the
+    /// lock restarts and the unclaimed remainder is folded into `reward_amount`.
+    pub reward_claimed_so_far: u64,
+    /// Part of `reward_amount` already earned when the lock started, claimable
+    /// at once instead of accruing over the lock.
+    pub vested_reward: u64,
+    /// When the rest of `reward_amount` starts accruing, if later than
+    /// `created_time`. Set by a weighted top-up so the reward it adds is not
+    /// earned for time that passed before it.
+    pub reward_start_time: i64,
+    /// Cooldown `request_unstake` applies, fixed when the position is opened.
+    pub cooldown_seconds: i64,
+    /// When the principal of a position in cooldown can be withdrawn. Zero
+    /// until `request_unstake` is called.
+    pub cooldown_end_time: i64,
+    /// Receipt minted by `stake_zbcn`. When set, the position is managed only
+    /// by whoever holds the receipt and unstaking burns it.
+    pub receipt_mint: Option<Pubkey>,
+    /// Account the staker lets unstake, extend or renew the position on their
+    /// behalf. Payouts still go to the staker.
+    pub delegate: Option<Pubkey>,
 }
 
 #[account]
@@ -20,14 +57,81 @@ pub struct UserNonce {
 }
 
 impl UserStakeData {
+    pub fn lock_period(&self) -> Result<LockPeriod> {
+        LockPeriod::from_seconds(self.lock_period)
+    }
+
     pub fn is_ended(&self) -> Result<bool> {
-        let current_time = Clock::get()?.unix_timestamp;
-        Ok(self.lock_period * 86400 + self.created_time < current_time)
+        self.is_ended_at(Clock::get()?.unix_timestamp)
+    }
+
+    pub fn is_ended_at(&self, current_time: i64) -> Result<bool> {
+        self.lock_period()?.has_ended(self.created_time, current_time)
+    }
+
+    /// Whether `request_unstake` has been called and the position awaits `withdraw`.
+    pub fn is_unbonding(&self) -> bool {
+        self.cooldown_end_time != 0
+    }
+
+    /// Whether `authority` is the staker or their delegate.
+    pub fn can_manage(&self, authority: Pubkey) -> bool {
+        self.staker == authority || self.delegate == Some(authority)
+    }
+
+    /// Wallet paid when `signer` exits the position: the receipt holder for a
+    /// tokenized position, otherwise the staker even if a delegate signs.
+    pub fn beneficiary(&self, signer: Pubkey) -> Pubkey {
+        if self.is_tokenized() {
+            signer
+        } else {
+            self.staker
+        }
+    }
+
+    /// Whether the position is represented by a receipt mint.
+    pub fn is_tokenized(&self) -> bool {
+        self.receipt_mint.is_some()
+    }
+
+    /// Reward still owed to the position.
+    pub fn unclaimed_reward(&self) -> u64 {
+        self.reward_amount.saturating_sub(self.reward_claimed_so_far)
+    }
+
+    /// Reward earned by `current_time` that has not been claimed yet.
+    pub fn claimable_reward_at(&self, current_time: i64) -> Result<u64> {
+        Ok(self
+            .accrued_reward_at(current_time)?
+            .saturating_sub(self.reward_claimed_so_far))
+    }
+
+    /// Share of `reward_amount` earned by `current_time`: the vested part, plus
+    /// the rest accruing linearly until the lock ends and capped once it has
+    /// matured.
+    pub fn accrued_reward_at(&self, current_time: i64) -> Result<u64> {
+        let end_time = self.lock_period()?.end_time(self.created_time)?;
+        let start_time = self.created_time.max(self.reward_start_time);
+        let accrual_period = end_time.saturating_sub(start_time);
+        if accrual_period <= 0 {
+            return Ok(self.reward_amount);
+        }
+        let elapsed = current_time
+            .saturating_sub(start_time)
+            .clamp(0, accrual_period);
+        let vested_reward = self.vested_reward.min(self.reward_amount);
+        let accruing = mul_div(
+            self.reward_amount - vested_reward,
+            elapsed as u128,
+            accrual_period as u128,
+            Rounding::Down,
+        )?;
+        Ok(vested_reward + accruing)
     }
 }
 
 impl UserNonce {
     pub fn get_nonce(&self) -> u64 {
-        return self.nonce;
+        self.nonce
     }
 }
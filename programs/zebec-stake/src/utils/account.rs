@@ -0,0 +1,29 @@
+use anchor_lang::{
+    prelude::*,
+    system_program::{transfer, Transfer},
+};
+
+/// Grows a program-owned account to `new_len`, topping up rent from `payer`.
+/// Newly added bytes are zeroed.
+pub fn resize_account<'info>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    new_len: usize,
+) -> Result<()> {
+    let rent_due = Rent::get()?
+        .minimum_balance(new_len)
+        .saturating_sub(account.lamports());
+    if rent_due > 0 {
+        let cpi_ctx = CpiContext::new(
+            system_program.clone(),
+            Transfer {
+                from: payer.clone(),
+                to: account.clone(),
+            },
+        );
+        transfer(cpi_ctx, rent_due)?;
+    }
+    account.resize(new_len)?;
+    Ok(())
+}
@@ -0,0 +1,75 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ZbcnStakeError;
+
+pub const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// Length of a stake lock in seconds. Seconds are the canonical unit for
+/// `duration_map`, `UserStakeData::lock_period`, maturity checks and the
+/// reward formula; construct through this type rather than raw integers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct LockPeriod(i64);
+
+impl LockPeriod {
+    pub fn from_seconds(seconds: i64) -> Result<Self> {
+        require!(seconds > 0, ZbcnStakeError::InvalidLockPeriod);
+        Ok(Self(seconds))
+    }
+
+    pub fn from_days(days: i64) -> Result<Self> {
+        let seconds = days
+            .checked_mul(SECONDS_PER_DAY)
+            .ok_or(ZbcnStakeError::InvalidLockPeriod)?;
+        Self::from_seconds(seconds)
+    }
+
+    pub fn as_seconds(self) -> i64 {
+        self.0
+    }
+
+    /// Key under which this period is stored in `duration_map`.
+    pub fn duration_key(self) -> u64 {
+        self.0 as u64
+    }
+
+    pub fn end_time(self, start_time: i64) -> Result<i64> {
+        start_time
+            .checked_add(self.0)
+            .ok_or(ZbcnStakeError::MathOverflow.into())
+    }
+
+    pub fn has_ended(self, start_time: i64, current_time: i64) -> Result<bool> {
+        Ok(current_time > self.end_time(start_time)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_non_positive_periods() {
+        assert!(LockPeriod::from_seconds(0).is_err());
+        assert!(LockPeriod::from_seconds(-1).is_err());
+        assert!(LockPeriod::from_days(0).is_err());
+        assert!(LockPeriod::from_days(i64::MAX).is_err());
+    }
+
+    #[test]
+    fn days_convert_to_seconds() {
+        let period = LockPeriod::from_days(90).unwrap();
+        assert_eq!(period, LockPeriod::from_seconds(7_776_000).unwrap());
+        assert_eq!(period.duration_key(), 7_776_000);
+    }
+
+    #[test]
+    fn ends_strictly_after_start_plus_period() {
+        let period = LockPeriod::from_days(30).unwrap();
+        let start = 1_700_000_000;
+        let end = start + 30 * SECONDS_PER_DAY;
+        assert_eq!(period.end_time(start).unwrap(), end);
+        assert!(!period.has_ended(start, end).unwrap());
+        assert!(period.has_ended(start, end + 1).unwrap());
+        assert!(period.end_time(i64::MAX).is_err());
+    }
+}
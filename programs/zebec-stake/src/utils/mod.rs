@@ -0,0 +1,11 @@
+pub mod account;
+pub mod lock_period;
+pub mod receipt;
+pub mod reward;
+pub mod token;
+
+pub use account::*;
+pub use lock_period::*;
+pub use receipt::*;
+pub use reward::*;
+pub use token::*;
@@ -0,0 +1,56 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token_2022::Token2022,
+    token_interface::{burn, Burn, Mint, TokenAccount},
+};
+
+use crate::error::ZbcnStakeError;
+
+/// Checks that `holder` owns the single receipt of a tokenized position.
+pub fn require_receipt_holder(
+    receipt_mint: Pubkey,
+    mint: Option<&InterfaceAccount<Mint>>,
+    holder_account: Option<&InterfaceAccount<TokenAccount>>,
+    holder: Pubkey,
+) -> Result<()> {
+    let (Some(mint), Some(holder_account)) = (mint, holder_account) else {
+        return err!(ZbcnStakeError::ReceiptRequired);
+    };
+    require!(
+        mint.key() == receipt_mint
+            && holder_account.mint == receipt_mint
+            && holder_account.owner == holder
+            && holder_account.amount == 1,
+        ZbcnStakeError::ReceiptRequired
+    );
+
+    Ok(())
+}
+
+/// Burns the holder's receipt once the position's principal is released.
+pub fn burn_receipt<'info>(
+    receipt_mint: Pubkey,
+    mint: Option<&InterfaceAccount<'info, Mint>>,
+    holder_account: Option<&InterfaceAccount<'info, TokenAccount>>,
+    token_program: Option<&Program<'info, Token2022>>,
+    holder: AccountInfo<'info>,
+) -> Result<()> {
+    require_receipt_holder(receipt_mint, mint, holder_account, holder.key())?;
+    let (Some(mint), Some(holder_account), Some(token_program)) =
+        (mint, holder_account, token_program)
+    else {
+        return err!(ZbcnStakeError::ReceiptRequired);
+    };
+
+    burn(
+        CpiContext::new(
+            token_program.to_account_info(),
+            Burn {
+                mint: mint.to_account_info(),
+                from: holder_account.to_account_info(),
+                authority: holder,
+            },
+        ),
+        1,
+    )
+}
@@ -0,0 +1,129 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{FEE_DENOMINATOR, REWARD_RATE_DENOMINATOR, SECONDS_PER_YEAR},
+    error::ZbcnStakeError,
+    utils::LockPeriod,
+};
+
+/// Direction in which a fractional base unit is resolved.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rounding {
+    Down,
+    Up,
+}
+
+/// Computes `value * numerator / denominator` in u128 without intermediate
+/// precision loss, resolving the remainder with `rounding`.
+pub fn mul_div(value: u64, numerator: u128, denominator: u128, rounding: Rounding) -> Result<u64> {
+    require!(denominator > 0, ZbcnStakeError::MathOverflow);
+
+    let product = (value as u128)
+        .checked_mul(numerator)
+        .ok_or(ZbcnStakeError::MathOverflow)?;
+    let quotient = product / denominator;
+    let result = match rounding {
+        Rounding::Down => quotient,
+        Rounding::Up if product % denominator != 0 => quotient + 1,
+        Rounding::Up => quotient,
+    };
+
+    u64::try_from(result).map_err(|_| ZbcnStakeError::MathOverflow.into())
+}
+
+/// Reward earned by `amount` locked for `lock_period` at `annual_rate` basis
+/// points. Rounds down so the program never pays out more than it owes.
+pub fn calculate_reward(amount: u64, annual_rate: u64, lock_period: LockPeriod) -> Result<u64> {
+    let numerator = (annual_rate as u128)
+        .checked_mul(lock_period.as_seconds() as u128)
+        .ok_or(ZbcnStakeError::MathOverflow)?;
+    let denominator = REWARD_RATE_DENOMINATOR as u128 * SECONDS_PER_YEAR as u128;
+
+    mul_div(amount, numerator, denominator, Rounding::Down)
+}
+
+/// Unstake fee charged on `amount` at `fee` tenths of a percent. Rounds down,
+/// as the fee was always computed, so the staker never pays a fractional unit.
+pub fn calculate_fee(amount: u64, fee: u64) -> Result<u64> {
+    mul_div(amount, fee as u128, FEE_DENOMINATOR as u128, Rounding::Down)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ZBCN: u64 = 1_000_000;
+
+    /// Tiers offered by the reference lockup: (lock period in days, annual rate in bps).
+    const DURATION_MAP: [(i64, u64); 3] = [(30, 800), (90, 1200), (120, 1500)];
+
+    fn days(days: i64) -> LockPeriod {
+        LockPeriod::from_days(days).unwrap()
+    }
+
+    #[test]
+    fn mul_div_rounds_in_requested_direction() {
+        assert_eq!(mul_div(10, 1, 3, Rounding::Down).unwrap(), 3);
+        assert_eq!(mul_div(10, 1, 3, Rounding::Up).unwrap(), 4);
+        assert_eq!(mul_div(9, 1, 3, Rounding::Up).unwrap(), 3);
+        assert_eq!(mul_div(0, 7, 3, Rounding::Up).unwrap(), 0);
+    }
+
+    #[test]
+    fn mul_div_rejects_overflow_and_zero_denominator() {
+        assert!(mul_div(u64::MAX, 2, 1, Rounding::Down).is_err());
+        assert!(mul_div(1, 1, 0, Rounding::Down).is_err());
+        assert_eq!(mul_div(u64::MAX, 1, 1, Rounding::Down).unwrap(), u64::MAX);
+    }
+
+    #[test]
+    fn reward_for_every_duration_tier() {
+        let expected = [6_575_342, 29_589_041, 49_315_068];
+        for ((lock_period, rate), expected) in DURATION_MAP.iter().zip(expected) {
+            assert_eq!(
+                calculate_reward(1000 * ZBCN, *rate, days(*lock_period)).unwrap(),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn reward_for_single_unit_stake_rounds_to_zero() {
+        for (lock_period, rate) in DURATION_MAP {
+            assert_eq!(calculate_reward(1, rate, days(lock_period)).unwrap(), 0);
+        }
+    }
+
+    #[test]
+    fn reward_for_max_stake_is_exact() {
+        let expected = [
+            121_293_659_662_747_736,
+            545_821_468_482_364_814,
+            909_702_447_470_608_024,
+        ];
+        for ((lock_period, rate), expected) in DURATION_MAP.iter().zip(expected) {
+            assert_eq!(
+                calculate_reward(u64::MAX, *rate, days(*lock_period)).unwrap(),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn reward_rejects_oversized_result() {
+        assert!(calculate_reward(u64::MAX, 10_000, days(2 * 365)).is_err());
+        assert_eq!(calculate_reward(1000 * ZBCN, 0, days(90)).unwrap(), 0);
+    }
+
+    #[test]
+    fn fee_rounds_down() {
+        assert_eq!(calculate_fee(1000 * ZBCN, 50).unwrap(), 50 * ZBCN);
+        assert_eq!(calculate_fee(19, 50).unwrap(), 0);
+        assert_eq!(calculate_fee(1, 1).unwrap(), 0);
+        assert_eq!(
+            calculate_fee(u64::MAX, 50).unwrap(),
+            922_337_203_685_477_580
+        );
+        assert_eq!(calculate_fee(1000 * ZBCN, 0).unwrap(), 0);
+    }
+}
@@ -0,0 +1,25 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{transfer, Transfer};
+
+/// Transfers `amount` out of a vault token account, signing as the vault PDA
+/// described by `vault_seeds`.
+pub fn transfer_from_vault<'info>(
+    token_program: AccountInfo<'info>,
+    from: AccountInfo<'info>,
+    to: AccountInfo<'info>,
+    vault: AccountInfo<'info>,
+    vault_seeds: &[&[u8]],
+    amount: u64,
+) -> Result<()> {
+    let signer_seeds: &[&[&[u8]]] = &[vault_seeds];
+    let cpi_ctx = CpiContext::new_with_signer(
+        token_program,
+        Transfer {
+            from,
+            to,
+            authority: vault,
+        },
+        signer_seeds,
+    );
+    transfer(cpi_ctx, amount)
+}
@@ -5,6 +5,9 @@ export const SEEDS = {
 	lockup: "zebec_lockup",
 	stakeVault: "stake_vault",
 	rewardVault: "reward_vault",
+	pendingUpdate: "pending_update",
+	receiptMint: "receipt_mint",
+	liquidLockup: "zebec_liquid_lockup",
 };
 
 export type DurationMap = {
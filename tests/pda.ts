@@ -58,4 +58,29 @@ export function deriveRewardVaultAddress(lockup: Address, programId: Address ) {
 	);
 
 	return rewardVault;
+}
+export function derivePendingUpdateAddress(lockup: Address, programId: Address ) {
+	const [pendingUpdate] = PublicKey.findProgramAddressSync(
+		[utils.bytes.utf8.encode(SEEDS.pendingUpdate), translateAddress(lockup).toBuffer()],
+		translateAddress(programId),
+	);
+
+	return pendingUpdate;
+}
+export function deriveReceiptMintAddress(stake: Address, programId: Address ) {
+	const [receiptMint] = PublicKey.findProgramAddressSync(
+		[utils.bytes.utf8.encode(SEEDS.receiptMint), translateAddress(stake).toBuffer()],
+		translateAddress(programId),
+	);
+
+	return receiptMint;
+}
+
+export function deriveLiquidLockupAddress(name: string, programId: Address ) {
+	const [liquidLockup] = PublicKey.findProgramAddressSync(
+		[utils.bytes.utf8.encode(SEEDS.liquidLockup), utils.bytes.utf8.encode(name)],
+		translateAddress(programId),
+	);
+
+	return liquidLockup;
 }
\ No newline at end of file
@@ -7,7 +7,10 @@ import { Program } from "@coral-xyz/anchor";
 import {
   ASSOCIATED_TOKEN_PROGRAM_ID,
   getAssociatedTokenAddress,
+  getMint,
   getOrCreateAssociatedTokenAccount,
+  getTokenMetadata,
+  TOKEN_2022_PROGRAM_ID,
   TOKEN_PROGRAM_ID,
 } from "@solana/spl-token";
 import { Keypair, PublicKey } from "@solana/web3.js";
@@ -15,7 +18,10 @@ import { Keypair, PublicKey } from "@solana/web3.js";
 import { ZebecStake } from "../target/types/zebec_stake";
 import { InitConfigParams, rewardSchemes } from "./constants";
 import {
+  deriveLiquidLockupAddress,
   deriveLockupAddress,
+  derivePendingUpdateAddress,
+  deriveReceiptMintAddress,
   deriveRewardVaultAddress,
   deriveStakeVaultAddress,
   deriveUserNonceAddress,
@@ -24,11 +30,15 @@ import {
 import {
   createNewMint,
   daysToSeconds,
+  encodeLegacyLockupData,
+  encodeLegacyStakeData,
   fundTokenAccount,
   getFeeVault,
   getTokenAccountBalance,
   getUserNonceInfo,
   parseZbcnUnits,
+  transferReceipt,
+  warpForward,
 } from "./utils";
 
 /* 
@@ -242,9 +252,15 @@ describe("stake-zbcn", async () => {
           stakerTokenAccount: staker1Ata,
           stakeVault: stakeVault,
           stakeVaultTokenAccount: stakeVaultAta,
+          rewardToken: mint,
+          rewardVault,
+          rewardVaultTokenAccount: rewardVaultAta,
           tokenProgram: TOKEN_PROGRAM_ID,
           systemProgram: anchor.web3.SystemProgram.programId,
           userNonce: stakerNonce1,
+          receiptMint: null,
+          stakerReceiptAccount: null,
+          receiptTokenProgram: null,
         })
         .signers([staker1, feePayer])
         .rpc();
@@ -299,6 +315,7 @@ describe("stake-zbcn", async () => {
           rewardVaultTokenAccount: rewardVaultAta,
           stakerRewardTokenAccount: staker1Ata,
           staker: program.provider.publicKey,
+          beneficiary: program.provider.publicKey,
           lockup: lockup,
           stakePda: staker1Pda,
           stakeToken: mint,
@@ -310,6 +327,10 @@ describe("stake-zbcn", async () => {
           tokenProgram: TOKEN_PROGRAM_ID,
           systemProgram: anchor.web3.SystemProgram.programId,
           associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          rentPayer: null,
+          receiptMint: null,
+          stakerReceiptAccount: null,
+          receiptTokenProgram: null,
         })
         .signers([staker1, feePayer])
         .rpc();
@@ -380,9 +401,15 @@ describe("stake-zbcn", async () => {
           stakerTokenAccount: staker1Ata,
           stakeVault: stakeVault,
           stakeVaultTokenAccount: stakeVaultAta,
+          rewardToken: mint,
+          rewardVault,
+          rewardVaultTokenAccount: rewardVaultAta,
           systemProgram: anchor.web3.SystemProgram.programId,
           tokenProgram: TOKEN_PROGRAM_ID,
           associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          receiptMint: null,
+          stakerReceiptAccount: null,
+          receiptTokenProgram: null,
         })
         .signers([staker1, feePayer])
         .rpc();
@@ -431,9 +458,15 @@ describe("stake-zbcn", async () => {
           stakerTokenAccount: staker1Ata,
           stakeVault: stakeVault,
           stakeVaultTokenAccount: stakeVaultAta,
+          rewardToken: mint,
+          rewardVault,
+          rewardVaultTokenAccount: rewardVaultAta,
           tokenProgram: TOKEN_PROGRAM_ID,
           systemProgram: anchor.web3.SystemProgram.programId,
           userNonce: stakerNonce1,
+          receiptMint: null,
+          stakerReceiptAccount: null,
+          receiptTokenProgram: null,
         })
         .signers([staker1, feePayer])
         .rpc();
@@ -448,6 +481,7 @@ describe("stake-zbcn", async () => {
           rewardVaultTokenAccount: rewardVaultAta,
           stakerRewardTokenAccount: staker1Ata,
           staker: program.provider.publicKey,
+          beneficiary: program.provider.publicKey,
           lockup: lockup,
           stakePda: staker1Pda,
           stakeToken: mint,
@@ -459,6 +493,10 @@ describe("stake-zbcn", async () => {
           tokenProgram: TOKEN_PROGRAM_ID,
           systemProgram: anchor.web3.SystemProgram.programId,
           associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          rentPayer: null,
+          receiptMint: null,
+          stakerReceiptAccount: null,
+          receiptTokenProgram: null,
         })
         .signers([staker1, feePayer])
         .rpc();
@@ -505,9 +543,15 @@ describe("stake-zbcn", async () => {
           stakerTokenAccount: staker1Ata,
           stakeVault: stakeVault,
           stakeVaultTokenAccount: stakeVaultAta,
+          rewardToken: mint,
+          rewardVault,
+          rewardVaultTokenAccount: rewardVaultAta,
           systemProgram: anchor.web3.SystemProgram.programId,
           tokenProgram: TOKEN_PROGRAM_ID,
           associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          receiptMint: null,
+          stakerReceiptAccount: null,
+          receiptTokenProgram: null,
         })
         .signers([staker1, feePayer])
         .rpc();
@@ -539,6 +583,7 @@ describe("stake-zbcn", async () => {
           rewardVaultTokenAccount: rewardVaultAta,
           stakerRewardTokenAccount: staker1Ata,
           staker: program.provider.publicKey,
+          beneficiary: program.provider.publicKey,
           lockup: lockup,
           stakePda: staker1Pda,
           stakeToken: mint,
@@ -550,6 +595,10 @@ describe("stake-zbcn", async () => {
           tokenProgram: TOKEN_PROGRAM_ID,
           systemProgram: anchor.web3.SystemProgram.programId,
           associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          rentPayer: null,
+          receiptMint: null,
+          stakerReceiptAccount: null,
+          receiptTokenProgram: null,
         })
         .signers([staker1, feePayer])
         .rpc();
@@ -595,9 +644,15 @@ describe("stake-zbcn", async () => {
           stakerTokenAccount: staker1Ata,
           stakeVault: stakeVault,
           stakeVaultTokenAccount: stakeVaultAta,
+          rewardToken: mint,
+          rewardVault,
+          rewardVaultTokenAccount: rewardVaultAta,
           systemProgram: anchor.web3.SystemProgram.programId,
           tokenProgram: TOKEN_PROGRAM_ID,
           associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          receiptMint: null,
+          stakerReceiptAccount: null,
+          receiptTokenProgram: null,
         })
         .signers([staker1, feePayer])
         .rpc();
@@ -611,6 +666,7 @@ describe("stake-zbcn", async () => {
           rewardVaultTokenAccount: rewardVaultAta,
           stakerRewardTokenAccount: staker1Ata,
           staker: program.provider.publicKey,
+          beneficiary: program.provider.publicKey,
           lockup: lockup,
           stakePda: staker1Pda,
           stakeToken: mint,
@@ -622,6 +678,10 @@ describe("stake-zbcn", async () => {
           tokenProgram: TOKEN_PROGRAM_ID,
           systemProgram: anchor.web3.SystemProgram.programId,
           associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          rentPayer: null,
+          receiptMint: null,
+          stakerReceiptAccount: null,
+          receiptTokenProgram: null,
         })
         .signers([staker1, feePayer])
         .rpc();
@@ -630,6 +690,3426 @@ describe("stake-zbcn", async () => {
       error = err;
     }
     expect(error).not.to.be.undefined;
-    expect(error.message).to.include("StakeRewardNotClaimable.");
+    expect(error.message).to.include("LockPeriodNotOffered.");
+  });
+
+  it("pays the reward rate captured at stake time after the lockup is updated", async () => {
+    const nonceInfo = await getUserNonceInfo(program, stakerNonce1);
+    const nonce = nonceInfo ? nonceInfo.nonce : BigInt(0);
+    const staker1Pda = deriveStakeAddress(
+      staker1.publicKey,
+      lockup,
+      nonce,
+      program.programId
+    );
+    const amount = new anchor.BN(parseZbcnUnits(1000));
+
+    await program.methods
+      .stakeZbcn({
+        amount,
+        lockPeriod: new anchor.BN(daysToSeconds(90)),
+        nonce: new anchor.BN(nonce.toString()),
+      })
+      .accountsStrict({
+        staker: staker1.publicKey,
+        feePayer: feePayer.publicKey,
+        lockup: lockup,
+        stakePda: staker1Pda,
+        userNonce: stakerNonce1,
+        stakeToken: mint,
+        stakerTokenAccount: staker1Ata,
+        stakeVault: stakeVault,
+        stakeVaultTokenAccount: stakeVaultAta,
+        rewardToken: mint,
+        rewardVault,
+        rewardVaultTokenAccount: rewardVaultAta,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        receiptMint: null,
+        stakerReceiptAccount: null,
+        receiptTokenProgram: null,
+      })
+      .signers([staker1, feePayer])
+      .rpc();
+
+    const stakeData = await program.account.userStakeData.fetch(staker1Pda);
+    expect(stakeData.rewardRate.toNumber()).to.be.equal(1200);
+
+    // Double the 90 day rate after the position has been opened.
+    await program.methods
+      .updateLockup({
+        fee: lockUpData.fee,
+        feeVault: lockUpData.feeVault,
+        minimumStake: lockUpData.minimumStake,
+        durationMap: rewardSchemes.map((scheme) =>
+          scheme.duration.eq(new anchor.BN(daysToSeconds(90)))
+            ? { duration: scheme.duration, reward: new anchor.BN(2400) }
+            : scheme
+        ),
+      })
+      .accountsStrict({
+        updater: provider.publicKey,
+        lockup: lockup,
+      })
+      .rpc();
+
+    await warpForward(context, daysToSeconds(91));
+
+    const balanceBefore = await getTokenAccountBalance(provider, staker1Ata);
+
+    await program.methods
+      .unstakeZbcn(new anchor.BN(nonce.toString()))
+      .accountsStrict({
+        rewardToken: mint,
+        feePayer: feePayer.publicKey,
+        rewardVault,
+        rewardVaultTokenAccount: rewardVaultAta,
+        stakerRewardTokenAccount: staker1Ata,
+        staker: program.provider.publicKey,
+        beneficiary: program.provider.publicKey,
+        lockup: lockup,
+        stakePda: staker1Pda,
+        stakeToken: mint,
+        stakerTokenAccount: staker1Ata,
+        stakeVault: stakeVault,
+        stakeVaultTokenAccount: stakeVaultAta,
+        feeVault: feeVault,
+        feeVaultTokenAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        rentPayer: null,
+        receiptMint: null,
+        stakerReceiptAccount: null,
+        receiptTokenProgram: null,
+      })
+      .signers([staker1, feePayer])
+      .rpc();
+
+    const balanceAfter = await getTokenAccountBalance(provider, staker1Ata);
+
+    // 1000 * 0.12 / (365 * 86400) * (90 * 86400), the rate at stake time
+    expect(balanceAfter - balanceBefore).to.be.equal(
+      BigInt(amount.toString()) + BigInt(29589041)
+    );
+
+    await program.methods
+      .updateLockup({
+        fee: lockUpData.fee,
+        feeVault: lockUpData.feeVault,
+        minimumStake: lockUpData.minimumStake,
+        durationMap: rewardSchemes,
+      })
+      .accountsStrict({
+        updater: provider.publicKey,
+        lockup: lockup,
+      })
+      .rpc();
+  });
+
+  it("rejects a legacy lockup until it is migrated", async () => {
+    const legacyLockup = deriveLockupAddress("legacy-lockup", program.programId);
+
+    context.setAccount(legacyLockup, {
+      lamports: 100000000,
+      data: encodeLegacyLockupData(program, {
+        name: "legacy-lockup",
+        creator: provider.publicKey,
+        minimumStake: BigInt(lockUpData.minimumStake.toString()),
+        durationMap: rewardSchemes.map(({ duration, reward }) => ({
+          duration: BigInt(duration.toString()),
+          reward: BigInt(reward.toString()),
+        })),
+        rewardToken: mint,
+        stakedToken: mint,
+        totalStaked: BigInt(parseZbcnUnits(5000)),
+        fee: BigInt(lockUpData.fee.toString()),
+        feeVault: lockUpData.feeVault,
+      }),
+      owner: program.programId,
+      executable: false,
+    });
+
+    const setState = () =>
+      program.methods
+        .setLockupState({ active: {} })
+        .accountsStrict({
+          pauser: provider.publicKey,
+          lockup: legacyLockup,
+        })
+        .rpc();
+
+    // The padded legacy layout would otherwise read as a lockup with empty
+    // roles and counters.
+    let error: Error | undefined;
+    try {
+      await setState();
+    } catch (err) {
+      error = err;
+    }
+    expect(error).not.to.be.undefined;
+    expect(error.message).to.include("LockupNotMigrated");
+
+    await program.methods
+      .migrateLockup()
+      .accountsStrict({
+        creator: provider.publicKey,
+        lockup: legacyLockup,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+    await setState();
+
+    const migrated = await program.account.lockup.fetch(legacyLockup);
+    expect(migrated.stakedToken.totalStaked.toString()).to.be.equal(
+      parseZbcnUnits(5000).toString()
+    );
+    expect(migrated.legacyMigrationPending).to.be.true;
+  });
+
+  it("migrates a legacy stake account", async () => {
+    const legacyStaker = Keypair.generate();
+    const nonce = BigInt(0);
+    const legacyPda = deriveStakeAddress(
+      legacyStaker.publicKey,
+      lockup,
+      nonce,
+      program.programId
+    );
+    const currentClock = await client.getClock();
+
+    context.setAccount(legacyPda, {
+      lamports: 10000000,
+      data: encodeLegacyStakeData(program, {
+        nonce,
+        createdTime: currentClock.unixTimestamp,
+        stakedAmount: BigInt(parseZbcnUnits(1000)),
+        rewardAmount: BigInt(0),
+        stakeClaimed: false,
+        lockPeriod: BigInt(daysToSeconds(30)),
+        staker: legacyStaker.publicKey,
+        lockup,
+      }),
+      owner: program.programId,
+      executable: false,
+    });
+
+    await program.methods
+      .migrateStake(new anchor.BN(nonce.toString()))
+      .accountsStrict({
+        authority: provider.publicKey,
+        lockup: lockup,
+        staker: legacyStaker.publicKey,
+        stakePda: legacyPda,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    const migrated = await program.account.userStakeData.fetch(legacyPda);
+    expect(migrated.rewardRate.toNumber()).to.be.equal(800);
+    expect(migrated.fee.toNumber()).to.be.equal(0);
+    // 1000 * 0.08 / (365 * 86400) * (30 * 86400)
+    expect(migrated.rewardAmount.toNumber()).to.be.equal(6575342);
+    expect(migrated.staker.toBase58()).to.be.equal(
+      legacyStaker.publicKey.toBase58()
+    );
+
+    let error: Error | undefined;
+    try {
+      await program.methods
+        .migrateStake(new anchor.BN(nonce.toString()))
+        .accountsStrict({
+          authority: provider.publicKey,
+          lockup: lockup,
+          staker: legacyStaker.publicKey,
+          stakePda: legacyPda,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+    } catch (err) {
+      error = err;
+    }
+    expect(error).not.to.be.undefined;
+    expect(error.message).to.include("StakeAlreadyMigrated");
+  });
+
+  it("migrates a legacy stake account whose lock period is in days", async () => {
+    const legacyStaker = Keypair.generate();
+    const nonce = BigInt(0);
+    const legacyPda = deriveStakeAddress(
+      legacyStaker.publicKey,
+      lockup,
+      nonce,
+      program.programId
+    );
+    const currentClock = await client.getClock();
+
+    context.setAccount(legacyPda, {
+      lamports: 10000000,
+      data: encodeLegacyStakeData(program, {
+        nonce,
+        createdTime: currentClock.unixTimestamp,
+        stakedAmount: BigInt(parseZbcnUnits(1000)),
+        rewardAmount: BigInt(0),
+        stakeClaimed: false,
+        lockPeriod: BigInt(90),
+        staker: legacyStaker.publicKey,
+        lockup,
+      }),
+      owner: program.programId,
+      executable: false,
+    });
+
+    await program.methods
+      .migrateStake(new anchor.BN(nonce.toString()))
+      .accountsStrict({
+        authority: provider.publicKey,
+        lockup: lockup,
+        staker: legacyStaker.publicKey,
+        stakePda: legacyPda,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    const migrated = await program.account.userStakeData.fetch(legacyPda);
+    expect(migrated.lockPeriod.toNumber()).to.be.equal(daysToSeconds(90));
+    expect(migrated.rewardRate.toNumber()).to.be.equal(1200);
+    expect(migrated.rewardAmount.toNumber()).to.be.equal(29589041);
+  });
+
+  it("migrates a legacy stake account whose duration was removed", async () => {
+    const legacyStaker = Keypair.generate();
+    const nonce = BigInt(0);
+    const legacyPda = deriveStakeAddress(
+      legacyStaker.publicKey,
+      lockup,
+      nonce,
+      program.programId
+    );
+    const currentClock = await client.getClock();
+
+    // 60 days is not part of the lockup's duration map
+    context.setAccount(legacyPda, {
+      lamports: 10000000,
+      data: encodeLegacyStakeData(program, {
+        nonce,
+        createdTime: currentClock.unixTimestamp,
+        stakedAmount: BigInt(parseZbcnUnits(1000)),
+        rewardAmount: BigInt(0),
+        stakeClaimed: false,
+        lockPeriod: BigInt(daysToSeconds(60)),
+        staker: legacyStaker.publicKey,
+        lockup,
+      }),
+      owner: program.programId,
+      executable: false,
+    });
+
+    await program.methods
+      .migrateStake(new anchor.BN(nonce.toString()))
+      .accountsStrict({
+        authority: provider.publicKey,
+        lockup: lockup,
+        staker: legacyStaker.publicKey,
+        stakePda: legacyPda,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    const migrated = await program.account.userStakeData.fetch(legacyPda);
+    expect(migrated.lockPeriod.toNumber()).to.be.equal(daysToSeconds(60));
+    expect(migrated.rewardRate.toNumber()).to.be.equal(0);
+    expect(migrated.rewardAmount.toNumber()).to.be.equal(0);
+    expect(migrated.stakedAmount.toNumber()).to.be.equal(
+      parseZbcnUnits(1000)
+    );
+  });
+
+  it("migrates a legacy stake account with no lock period", async () => {
+    const legacyStaker = Keypair.generate();
+    const nonce = BigInt(0);
+    const legacyPda = deriveStakeAddress(
+      legacyStaker.publicKey,
+      lockup,
+      nonce,
+      program.programId
+    );
+    const currentClock = await client.getClock();
+
+    context.setAccount(legacyPda, {
+      lamports: 10000000,
+      data: encodeLegacyStakeData(program, {
+        nonce,
+        createdTime: currentClock.unixTimestamp,
+        stakedAmount: BigInt(parseZbcnUnits(1000)),
+        rewardAmount: BigInt(0),
+        stakeClaimed: false,
+        lockPeriod: BigInt(0),
+        staker: legacyStaker.publicKey,
+        lockup,
+      }),
+      owner: program.programId,
+      executable: false,
+    });
+
+    await program.methods
+      .migrateStake(new anchor.BN(nonce.toString()))
+      .accountsStrict({
+        authority: provider.publicKey,
+        lockup: lockup,
+        staker: legacyStaker.publicKey,
+        stakePda: legacyPda,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    // The position is stored as already matured so the principal can leave.
+    const migrated = await program.account.userStakeData.fetch(legacyPda);
+    expect(migrated.lockPeriod.toNumber()).to.be.equal(1);
+    expect(migrated.rewardRate.toNumber()).to.be.equal(0);
+    expect(migrated.rewardAmount.toNumber()).to.be.equal(0);
+    expect(migrated.fee.toNumber()).to.be.equal(0);
+    expect(migrated.stakedAmount.toNumber()).to.be.equal(
+      parseZbcnUnits(1000)
+    );
+  });
+
+  it("tracks lockup totals across stake and unstake", async () => {
+    const nonceInfo = await getUserNonceInfo(program, stakerNonce1);
+    const nonce = nonceInfo ? nonceInfo.nonce : BigInt(0);
+    const staker1Pda = deriveStakeAddress(
+      staker1.publicKey,
+      lockup,
+      nonce,
+      program.programId
+    );
+    const amount = new anchor.BN(parseZbcnUnits(1000));
+
+    // 5% unstake fee
+    await program.methods
+      .updateLockup({
+        fee: new anchor.BN(50),
+        feeVault: lockUpData.feeVault,
+        minimumStake: lockUpData.minimumStake,
+        durationMap: rewardSchemes,
+      })
+      .accountsStrict({
+        updater: provider.publicKey,
+        lockup: lockup,
+      })
+      .rpc();
+
+    const lockupBefore = await program.account.lockup.fetch(lockup);
+
+    await program.methods
+      .stakeZbcn({
+        amount,
+        lockPeriod: new anchor.BN(daysToSeconds(30)),
+        nonce: new anchor.BN(nonce.toString()),
+      })
+      .accountsStrict({
+        staker: staker1.publicKey,
+        feePayer: feePayer.publicKey,
+        lockup: lockup,
+        stakePda: staker1Pda,
+        userNonce: stakerNonce1,
+        stakeToken: mint,
+        stakerTokenAccount: staker1Ata,
+        stakeVault: stakeVault,
+        stakeVaultTokenAccount: stakeVaultAta,
+        rewardToken: mint,
+        rewardVault,
+        rewardVaultTokenAccount: rewardVaultAta,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        receiptMint: null,
+        stakerReceiptAccount: null,
+        receiptTokenProgram: null,
+      })
+      .signers([staker1, feePayer])
+      .rpc();
+
+    const lockupAfterStake = await program.account.lockup.fetch(lockup);
+    expect(
+      lockupAfterStake.stakedToken.totalStaked
+        .sub(lockupBefore.stakedToken.totalStaked)
+        .toString()
+    ).to.be.equal(amount.toString());
+    expect(lockupAfterStake.stats.activePositions.toNumber()).to.be.equal(
+      lockupBefore.stats.activePositions.toNumber() + 1
+    );
+    expect(
+      lockupAfterStake.stats.totalRewardLiability
+        .sub(lockupBefore.stats.totalRewardLiability)
+        .toNumber()
+    ).to.be.equal(6575342);
+
+    await warpForward(context, daysToSeconds(31));
+
+    const feeVaultBalanceBefore = await getTokenAccountBalance(
+      provider,
+      feeVaultTokenAccount
+    );
+
+    await program.methods
+      .unstakeZbcn(new anchor.BN(nonce.toString()))
+      .accountsStrict({
+        rewardToken: mint,
+        feePayer: feePayer.publicKey,
+        rewardVault,
+        rewardVaultTokenAccount: rewardVaultAta,
+        stakerRewardTokenAccount: staker1Ata,
+        staker: program.provider.publicKey,
+        beneficiary: program.provider.publicKey,
+        lockup: lockup,
+        stakePda: staker1Pda,
+        stakeToken: mint,
+        stakerTokenAccount: staker1Ata,
+        stakeVault: stakeVault,
+        stakeVaultTokenAccount: stakeVaultAta,
+        feeVault: feeVault,
+        feeVaultTokenAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        rentPayer: null,
+        receiptMint: null,
+        stakerReceiptAccount: null,
+        receiptTokenProgram: null,
+      })
+      .signers([staker1, feePayer])
+      .rpc();
+
+    const feeVaultBalanceAfter = await getTokenAccountBalance(
+      provider,
+      feeVaultTokenAccount
+    );
+    const expectedFee = BigInt(parseZbcnUnits(50));
+    // 1000 * 0.08 / (365 * 86400) * (30 * 86400)
+    const expectedReward = BigInt(6575342);
+
+    expect(feeVaultBalanceAfter - feeVaultBalanceBefore).to.be.equal(
+      expectedFee
+    );
+
+    const lockupAfterUnstake = await program.account.lockup.fetch(lockup);
+    expect(lockupAfterUnstake.stakedToken.totalStaked.toString()).to.be.equal(
+      lockupBefore.stakedToken.totalStaked.toString()
+    );
+    expect(lockupAfterUnstake.stats.activePositions.toNumber()).to.be.equal(
+      lockupBefore.stats.activePositions.toNumber()
+    );
+    expect(lockupAfterUnstake.stats.totalRewardLiability.toString()).to.be.equal(
+      lockupBefore.stats.totalRewardLiability.toString()
+    );
+    expect(
+      BigInt(
+        lockupAfterUnstake.stats.totalRewardsPaid
+          .sub(lockupBefore.stats.totalRewardsPaid)
+          .toString()
+      )
+    ).to.be.equal(expectedReward);
+    expect(
+      BigInt(
+        lockupAfterUnstake.stats.totalFeesCollected
+          .sub(lockupBefore.stats.totalFeesCollected)
+          .toString()
+      )
+    ).to.be.equal(expectedFee);
+
+    await program.methods
+      .updateLockup({
+        fee: lockUpData.fee,
+        feeVault: lockUpData.feeVault,
+        minimumStake: lockUpData.minimumStake,
+        durationMap: rewardSchemes,
+      })
+      .accountsStrict({
+        updater: provider.publicKey,
+        lockup: lockup,
+      })
+      .rpc();
+  });
+
+  it("counts only unclaimed whitelisted positions in lockup totals", async () => {
+    const whitelisted = Keypair.generate();
+    const whitelistedNonce = deriveUserNonceAddress(
+      whitelisted.publicKey,
+      lockup,
+      program.programId
+    );
+    const currentClock = await client.getClock();
+    const amount = new anchor.BN(parseZbcnUnits(2000));
+
+    const lockupBefore = await program.account.lockup.fetch(lockup);
+
+    for (const [nonce, claimed] of [
+      [0, true],
+      [1, false],
+    ] as const) {
+      await program.methods
+        .whitelistStaker({
+          amount,
+          nonce: new anchor.BN(nonce),
+          lockPeriod: new anchor.BN(daysToSeconds(90)),
+          createdTime: new anchor.BN(currentClock.unixTimestamp.toString()),
+          claimed,
+        })
+        .accountsStrict({
+          admin: provider.publicKey,
+          lockup: lockup,
+          userNonce: whitelistedNonce,
+          staker: whitelisted.publicKey,
+          stakePda: deriveStakeAddress(
+            whitelisted.publicKey,
+            lockup,
+            BigInt(nonce),
+            program.programId
+          ),
+          stakeToken: mint,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+    }
+
+    const lockupAfter = await program.account.lockup.fetch(lockup);
+    expect(
+      lockupAfter.stakedToken.totalStaked
+        .sub(lockupBefore.stakedToken.totalStaked)
+        .toString()
+    ).to.be.equal(amount.toString());
+    expect(lockupAfter.stats.activePositions.toNumber()).to.be.equal(
+      lockupBefore.stats.activePositions.toNumber() + 1
+    );
+  });
+
+  it("rejects stakes whose reward the reward vault cannot cover", async () => {
+    const nonceInfo = await getUserNonceInfo(program, stakerNonce1);
+    const nonce = nonceInfo ? nonceInfo.nonce : BigInt(0);
+    const staker1Pda = deriveStakeAddress(
+      staker1.publicKey,
+      lockup,
+      nonce,
+      program.programId
+    );
+    const lockupBefore = await program.account.lockup.fetch(lockup);
+
+    let error: Error | undefined;
+    try {
+      // 100,000 ZBCN at 15% for 120 days earns ~4931 ZBCN, more than the vault holds
+      await program.methods
+        .stakeZbcn({
+          amount: new anchor.BN(parseZbcnUnits(100000)),
+          lockPeriod: new anchor.BN(daysToSeconds(120)),
+          nonce: new anchor.BN(nonce.toString()),
+        })
+        .accountsStrict({
+          staker: staker1.publicKey,
+          feePayer: feePayer.publicKey,
+          lockup: lockup,
+          stakePda: staker1Pda,
+          userNonce: stakerNonce1,
+          stakeToken: mint,
+          stakerTokenAccount: staker1Ata,
+          stakeVault: stakeVault,
+          stakeVaultTokenAccount: stakeVaultAta,
+          rewardToken: mint,
+          rewardVault,
+          rewardVaultTokenAccount: rewardVaultAta,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          receiptMint: null,
+          stakerReceiptAccount: null,
+          receiptTokenProgram: null,
+        })
+        .signers([staker1, feePayer])
+        .rpc();
+    } catch (err) {
+      error = err;
+    }
+    expect(error).not.to.be.undefined;
+    expect(error.message).to.include("InsufficientRewardVault");
+
+    const lockupAfter = await program.account.lockup.fetch(lockup);
+    expect(lockupAfter.stats.totalRewardLiability.toString()).to.be.equal(
+      lockupBefore.stats.totalRewardLiability.toString()
+    );
+  });
+
+  it("funds the reward vault through the program", async () => {
+    const amount = new anchor.BN(parseZbcnUnits(500));
+    const vaultBalanceBefore = await getTokenAccountBalance(
+      provider,
+      rewardVaultAta
+    );
+
+    await program.methods
+      .fundRewards(amount)
+      .accountsStrict({
+        funder: provider.publicKey,
+        lockup: lockup,
+        rewardToken: mint,
+        funderTokenAccount: staker1Ata,
+        rewardVault,
+        rewardVaultTokenAccount: rewardVaultAta,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    const vaultBalanceAfter = await getTokenAccountBalance(
+      provider,
+      rewardVaultAta
+    );
+    expect(vaultBalanceAfter - vaultBalanceBefore).to.be.equal(
+      BigInt(amount.toString())
+    );
+  });
+
+  it("withdraws only rewards above outstanding liability", async () => {
+    const lockupAccount = await program.account.lockup.fetch(lockup);
+    const vaultBalance = await getTokenAccountBalance(provider, rewardVaultAta);
+    const excess =
+      vaultBalance -
+      BigInt(lockupAccount.stats.totalRewardLiability.toString());
+
+    const withdraw = (amount: bigint) =>
+      program.methods
+        .withdrawExcessRewards(new anchor.BN(amount.toString()))
+        .accountsStrict({
+          authority: provider.publicKey,
+          lockup: lockup,
+          rewardToken: mint,
+          rewardVault,
+          rewardVaultTokenAccount: rewardVaultAta,
+          destinationTokenAccount: staker1Ata,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+    let error: Error | undefined;
+    try {
+      await withdraw(excess + BigInt(1));
+    } catch (err) {
+      error = err;
+    }
+    expect(error).not.to.be.undefined;
+    expect(error.message).to.include("WithdrawExceedsExcess");
+
+    const amount = BigInt(parseZbcnUnits(100));
+    await withdraw(amount);
+
+    expect(await getTokenAccountBalance(provider, rewardVaultAta)).to.be.equal(
+      vaultBalance - amount
+    );
+  });
+
+  it("hands over lockup authority in two steps", async () => {
+    const newAuthority = Keypair.generate();
+    const intruder = Keypair.generate();
+
+    await program.methods
+      .proposeAuthority(newAuthority.publicKey)
+      .accountsStrict({
+        authority: provider.publicKey,
+        lockup: lockup,
+      })
+      .rpc();
+
+    let lockupAccount = await program.account.lockup.fetch(lockup);
+    expect(lockupAccount.stakeInfo.creator.toBase58()).to.be.equal(
+      provider.publicKey.toBase58()
+    );
+    expect(lockupAccount.pendingAuthority.toBase58()).to.be.equal(
+      newAuthority.publicKey.toBase58()
+    );
+
+    let error: Error | undefined;
+    try {
+      await program.methods
+        .acceptAuthority()
+        .accountsStrict({
+          newAuthority: intruder.publicKey,
+          lockup: lockup,
+        })
+        .signers([intruder])
+        .rpc();
+    } catch (err) {
+      error = err;
+    }
+    expect(error).not.to.be.undefined;
+    expect(error.message).to.include("UnAuthorized");
+
+    await program.methods
+      .acceptAuthority()
+      .accountsStrict({
+        newAuthority: newAuthority.publicKey,
+        lockup: lockup,
+      })
+      .signers([newAuthority])
+      .rpc();
+
+    lockupAccount = await program.account.lockup.fetch(lockup);
+    expect(lockupAccount.stakeInfo.creator.toBase58()).to.be.equal(
+      newAuthority.publicKey.toBase58()
+    );
+    expect(lockupAccount.pendingAuthority).to.be.null;
+    expect(lockupAccount.roles.configAdmin.toBase58()).to.be.equal(
+      newAuthority.publicKey.toBase58()
+    );
+    expect(lockupAccount.roles.feeAdmin.toBase58()).to.be.equal(
+      newAuthority.publicKey.toBase58()
+    );
+    expect(lockupAccount.roles.operator.toBase58()).to.be.equal(
+      newAuthority.publicKey.toBase58()
+    );
+    expect(lockupAccount.roles.pauser.toBase58()).to.be.equal(
+      newAuthority.publicKey.toBase58()
+    );
+
+    // The previous authority keeps no role once the handover is accepted.
+    error = undefined;
+    try {
+      await program.methods
+        .updateLockup({
+          fee: lockUpData.fee,
+          feeVault: lockUpData.feeVault,
+          minimumStake: new anchor.BN(1),
+          durationMap: rewardSchemes,
+        })
+        .accountsStrict({
+          updater: provider.publicKey,
+          lockup: lockup,
+        })
+        .rpc();
+    } catch (err) {
+      error = err;
+    }
+    expect(error).not.to.be.undefined;
+    expect(error.message).to.include("UnAuthorized");
+
+    // Hand control back for the remaining tests.
+    await program.methods
+      .proposeAuthority(provider.publicKey)
+      .accountsStrict({
+        authority: newAuthority.publicKey,
+        lockup: lockup,
+      })
+      .signers([newAuthority])
+      .rpc();
+    await program.methods
+      .acceptAuthority()
+      .accountsStrict({
+        newAuthority: provider.publicKey,
+        lockup: lockup,
+      })
+      .rpc();
+  });
+
+  it("requires the fee admin role to change fees", async () => {
+    const feeAdmin = Keypair.generate();
+
+    await program.methods
+      .grantRole({ feeAdmin: {} }, feeAdmin.publicKey)
+      .accountsStrict({
+        authority: provider.publicKey,
+        lockup: lockup,
+      })
+      .rpc();
+
+    let lockupAccount = await program.account.lockup.fetch(lockup);
+    expect(lockupAccount.roles.feeAdmin.toBase58()).to.be.equal(
+      feeAdmin.publicKey.toBase58()
+    );
+    expect(lockupAccount.roles.configAdmin.toBase58()).to.be.equal(
+      provider.publicKey.toBase58()
+    );
+
+    let error: Error | undefined;
+    try {
+      await program.methods
+        .updateLockup({
+          fee: new anchor.BN(10),
+          feeVault: lockUpData.feeVault,
+          minimumStake: lockUpData.minimumStake,
+          durationMap: rewardSchemes,
+        })
+        .accountsStrict({
+          updater: provider.publicKey,
+          lockup: lockup,
+        })
+        .rpc();
+    } catch (err) {
+      error = err;
+    }
+    expect(error).not.to.be.undefined;
+    expect(error.message).to.include("UnAuthorized");
+
+    // The config admin can still update everything but the fee settings.
+    await program.methods
+      .updateLockup({
+        fee: lockUpData.fee,
+        feeVault: lockUpData.feeVault,
+        minimumStake: lockUpData.minimumStake,
+        durationMap: rewardSchemes,
+      })
+      .accountsStrict({
+        updater: provider.publicKey,
+        lockup: lockup,
+      })
+      .rpc();
+
+    // A wallet holding neither role cannot apply even an unchanged update.
+    const outsider = Keypair.generate();
+    error = undefined;
+    try {
+      await program.methods
+        .updateLockup({
+          fee: lockUpData.fee,
+          feeVault: lockUpData.feeVault,
+          minimumStake: lockUpData.minimumStake,
+          durationMap: rewardSchemes,
+        })
+        .accountsStrict({
+          updater: outsider.publicKey,
+          lockup: lockup,
+        })
+        .signers([outsider])
+        .rpc();
+    } catch (err) {
+      error = err;
+    }
+    expect(error).not.to.be.undefined;
+    expect(error.message).to.include("UnAuthorized");
+
+    // The fee admin alone can change the fee settings.
+    await program.methods
+      .updateLockup({
+        fee: new anchor.BN(10),
+        feeVault: lockUpData.feeVault,
+        minimumStake: lockUpData.minimumStake,
+        durationMap: rewardSchemes,
+      })
+      .accountsStrict({
+        updater: feeAdmin.publicKey,
+        lockup: lockup,
+      })
+      .signers([feeAdmin])
+      .rpc();
+
+    lockupAccount = await program.account.lockup.fetch(lockup);
+    expect(lockupAccount.feeInfo.fee.toNumber()).to.be.equal(10);
+
+    await program.methods
+      .updateLockup({
+        fee: lockUpData.fee,
+        feeVault: lockUpData.feeVault,
+        minimumStake: lockUpData.minimumStake,
+        durationMap: rewardSchemes,
+      })
+      .accountsStrict({
+        updater: feeAdmin.publicKey,
+        lockup: lockup,
+      })
+      .signers([feeAdmin])
+      .rpc();
+
+    await program.methods
+      .revokeRole({ feeAdmin: {} })
+      .accountsStrict({
+        authority: provider.publicKey,
+        lockup: lockup,
+      })
+      .rpc();
+
+    lockupAccount = await program.account.lockup.fetch(lockup);
+    expect(lockupAccount.roles.feeAdmin.toBase58()).to.be.equal(
+      PublicKey.default.toBase58()
+    );
+
+    await program.methods
+      .grantRole({ feeAdmin: {} }, provider.publicKey)
+      .accountsStrict({
+        authority: provider.publicKey,
+        lockup: lockup,
+      })
+      .rpc();
+  });
+
+  it("pauses staking and lets positions exit in emergency mode", async () => {
+    const nonceInfo = await getUserNonceInfo(program, stakerNonce1);
+    const nonce = nonceInfo ? nonceInfo.nonce : BigInt(0);
+    const staker1Pda = deriveStakeAddress(
+      staker1.publicKey,
+      lockup,
+      nonce,
+      program.programId
+    );
+    const amount = new anchor.BN(parseZbcnUnits(1000));
+
+    const stake = () =>
+      program.methods
+        .stakeZbcn({
+          amount,
+          lockPeriod: new anchor.BN(daysToSeconds(90)),
+          nonce: new anchor.BN(nonce.toString()),
+        })
+        .accountsStrict({
+          staker: staker1.publicKey,
+          feePayer: feePayer.publicKey,
+          lockup: lockup,
+          stakePda: staker1Pda,
+          userNonce: stakerNonce1,
+          stakeToken: mint,
+          stakerTokenAccount: staker1Ata,
+          stakeVault: stakeVault,
+          stakeVaultTokenAccount: stakeVaultAta,
+          rewardToken: mint,
+          rewardVault,
+          rewardVaultTokenAccount: rewardVaultAta,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          receiptMint: null,
+          stakerReceiptAccount: null,
+          receiptTokenProgram: null,
+        })
+        .signers([staker1, feePayer])
+        .rpc();
+
+    const setState = (state: any) =>
+      program.methods
+        .setLockupState(state)
+        .accountsStrict({
+          pauser: provider.publicKey,
+          lockup: lockup,
+        })
+        .rpc();
+
+    await setState({ stakingPaused: {} });
+
+    let error: Error | undefined;
+    try {
+      await stake();
+    } catch (err) {
+      error = err;
+    }
+    expect(error).not.to.be.undefined;
+    expect(error.message).to.include("StakingPaused");
+
+    await setState({ active: {} });
+    await stake();
+
+    const lockupBefore = await program.account.lockup.fetch(lockup);
+    const stakerBalanceBefore = await getTokenAccountBalance(
+      provider,
+      staker1Ata
+    );
+
+    await setState({ emergencyWithdraw: {} });
+
+    // Still inside the 90 day lock, yet the principal comes back in full.
+    await program.methods
+      .unstakeZbcn(new anchor.BN(nonce.toString()))
+      .accountsStrict({
+        rewardToken: mint,
+        feePayer: feePayer.publicKey,
+        rewardVault,
+        rewardVaultTokenAccount: rewardVaultAta,
+        stakerRewardTokenAccount: staker1Ata,
+        staker: program.provider.publicKey,
+        beneficiary: program.provider.publicKey,
+        lockup: lockup,
+        stakePda: staker1Pda,
+        stakeToken: mint,
+        stakerTokenAccount: staker1Ata,
+        stakeVault: stakeVault,
+        stakeVaultTokenAccount: stakeVaultAta,
+        feeVault: feeVault,
+        feeVaultTokenAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        rentPayer: null,
+        receiptMint: null,
+        stakerReceiptAccount: null,
+        receiptTokenProgram: null,
+      })
+      .signers([staker1, feePayer])
+      .rpc();
+
+    const stakerBalanceAfter = await getTokenAccountBalance(
+      provider,
+      staker1Ata
+    );
+    expect(stakerBalanceAfter - stakerBalanceBefore).to.be.equal(
+      BigInt(amount.toString())
+    );
+
+    const stakeAccount = await program.account.userStakeData.fetch(staker1Pda);
+    expect(stakeAccount.stakeClaimed).to.be.true;
+    expect(stakeAccount.rewardAmount.toNumber()).to.be.equal(0);
+
+    const lockupAfter = await program.account.lockup.fetch(lockup);
+    expect(lockupAfter.stats.activePositions.toNumber()).to.be.equal(
+      lockupBefore.stats.activePositions.toNumber() - 1
+    );
+    expect(lockupAfter.stats.totalRewardLiability.toNumber()).to.be.equal(
+      lockupBefore.stats.totalRewardLiability.toNumber() - 29589041
+    );
+    expect(lockupAfter.stats.totalRewardsPaid.toString()).to.be.equal(
+      lockupBefore.stats.totalRewardsPaid.toString()
+    );
+
+    await setState({ active: {} });
+  });
+
+  it("applies timelocked lockup updates only after the delay", async () => {
+    const pendingUpdate = derivePendingUpdateAddress(lockup, program.programId);
+    const delay = daysToSeconds(1);
+    const params = {
+      fee: lockUpData.fee,
+      feeVault: lockUpData.feeVault,
+      minimumStake: lockUpData.minimumStake,
+      durationMap: rewardSchemes,
+    };
+
+    const queue = (minimumStake: anchor.BN, updateDelay: anchor.BN | null) =>
+      program.methods
+        .queueLockupUpdate({ ...params, minimumStake }, updateDelay)
+        .accountsStrict({
+          updater: provider.publicKey,
+          lockup: lockup,
+          pendingUpdate,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+    // Execution is permissionless; a fresh executor keeps every call distinct.
+    const execute = () => {
+      const executor = Keypair.generate();
+      return program.methods
+        .executeLockupUpdate()
+        .accountsStrict({
+          executor: executor.publicKey,
+          lockup: lockup,
+          pendingUpdate,
+          payer: provider.publicKey,
+        })
+        .signers([executor])
+        .rpc();
+    };
+
+    // Queueing needs an admin role even when nothing would change, so no
+    // outsider can squat on the pending update slot.
+    let error: Error | undefined;
+    try {
+      await program.methods
+        .queueLockupUpdate(params, new anchor.BN(daysToSeconds(3650)))
+        .accountsStrict({
+          updater: feePayer.publicKey,
+          lockup: lockup,
+          pendingUpdate,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([feePayer])
+        .rpc();
+    } catch (err) {
+      error = err;
+    }
+    expect(error).not.to.be.undefined;
+    expect(error.message).to.include("UnAuthorized");
+    expect(await client.getAccount(pendingUpdate)).to.be.null;
+
+    // With no delay in force the update that introduces one applies at once.
+    await queue(lockUpData.minimumStake, new anchor.BN(delay));
+    await execute();
+
+    let lockupAccount = await program.account.lockup.fetch(lockup);
+    expect(lockupAccount.updateDelay.toNumber()).to.be.equal(delay);
+
+    error = undefined;
+    try {
+      await program.methods
+        .updateLockup(params)
+        .accountsStrict({
+          updater: provider.publicKey,
+          lockup: lockup,
+        })
+        .rpc();
+    } catch (err) {
+      error = err;
+    }
+    expect(error).not.to.be.undefined;
+    expect(error.message).to.include("UpdateTimelocked");
+
+    const newMinimumStake = new anchor.BN(parseZbcnUnits(2));
+    await queue(newMinimumStake, null);
+
+    const pending = await program.account.pendingLockupUpdate.fetch(
+      pendingUpdate
+    );
+    expect(pending.effectiveTime.sub(pending.queuedTime).toNumber()).to.be.equal(
+      delay
+    );
+
+    error = undefined;
+    try {
+      await execute();
+    } catch (err) {
+      error = err;
+    }
+    expect(error).not.to.be.undefined;
+    expect(error.message).to.include("UpdateNotReady");
+
+    await warpForward(context, delay);
+    await execute();
+
+    lockupAccount = await program.account.lockup.fetch(lockup);
+    expect(lockupAccount.stakeInfo.minimumStake.toString()).to.be.equal(
+      newMinimumStake.toString()
+    );
+    expect(await client.getAccount(pendingUpdate)).to.be.null;
+
+    // A cancelled update never applies.
+    await queue(new anchor.BN(parseZbcnUnits(3)), null);
+    await program.methods
+      .cancelLockupUpdate()
+      .accountsStrict({
+        canceller: provider.publicKey,
+        lockup: lockup,
+        pendingUpdate,
+        payer: provider.publicKey,
+      })
+      .rpc();
+    expect(await client.getAccount(pendingUpdate)).to.be.null;
+
+    // Restore the original settings and remove the delay.
+    await queue(lockUpData.minimumStake, new anchor.BN(0));
+    await warpForward(context, delay);
+    await execute();
+
+    lockupAccount = await program.account.lockup.fetch(lockup);
+    expect(lockupAccount.updateDelay.toNumber()).to.be.equal(0);
+    expect(lockupAccount.stakeInfo.minimumStake.toString()).to.be.equal(
+      lockUpData.minimumStake.toString()
+    );
+  });
+
+  it("withdraws part of a matured position and relocks the rest", async () => {
+    const nonceInfo = await getUserNonceInfo(program, stakerNonce1);
+    const nonce = nonceInfo ? nonceInfo.nonce : BigInt(0);
+    const staker1Pda = deriveStakeAddress(
+      staker1.publicKey,
+      lockup,
+      nonce,
+      program.programId
+    );
+
+    await program.methods
+      .stakeZbcn({
+        amount: new anchor.BN(parseZbcnUnits(2000)),
+        lockPeriod: new anchor.BN(daysToSeconds(30)),
+        nonce: new anchor.BN(nonce.toString()),
+      })
+      .accountsStrict({
+        staker: staker1.publicKey,
+        feePayer: feePayer.publicKey,
+        lockup: lockup,
+        stakePda: staker1Pda,
+        userNonce: stakerNonce1,
+        stakeToken: mint,
+        stakerTokenAccount: staker1Ata,
+        stakeVault: stakeVault,
+        stakeVaultTokenAccount: stakeVaultAta,
+        rewardToken: mint,
+        rewardVault,
+        rewardVaultTokenAccount: rewardVaultAta,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        receiptMint: null,
+        stakerReceiptAccount: null,
+        receiptTokenProgram: null,
+      })
+      .signers([staker1, feePayer])
+      .rpc();
+
+    await warpForward(context, daysToSeconds(31));
+
+    const lockupBefore = await program.account.lockup.fetch(lockup);
+    const stakerBalanceBefore = await getTokenAccountBalance(
+      provider,
+      staker1Ata
+    );
+
+    await program.methods
+      .partialUnstake({
+        nonce: new anchor.BN(nonce.toString()),
+        amount: new anchor.BN(parseZbcnUnits(1000)),
+        lockPeriod: new anchor.BN(daysToSeconds(90)),
+      })
+      .accountsStrict({
+        staker: staker1.publicKey,
+        feePayer: feePayer.publicKey,
+        lockup: lockup,
+        stakePda: staker1Pda,
+        rewardToken: mint,
+        stakeToken: mint,
+        stakerTokenAccount: staker1Ata,
+        stakerRewardTokenAccount: staker1Ata,
+        stakeVault: stakeVault,
+        rewardVault,
+        stakeVaultTokenAccount: stakeVaultAta,
+        rewardVaultTokenAccount: rewardVaultAta,
+        feeVaultTokenAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([staker1, feePayer])
+      .rpc();
+
+    // Half of the 30 day reward on 2000 ZBCN (13150684) is paid out.
+    const paidReward = 6575342;
+    // 1000 * 0.12 / (365 * 86400) * (90 * 86400)
+    const newReward = 29589041;
+
+    const stakerBalanceAfter = await getTokenAccountBalance(
+      provider,
+      staker1Ata
+    );
+    expect(stakerBalanceAfter - stakerBalanceBefore).to.be.equal(
+      BigInt(parseZbcnUnits(1000)) + BigInt(paidReward)
+    );
+
+    const stakeAccount = await program.account.userStakeData.fetch(staker1Pda);
+    expect(stakeAccount.stakeClaimed).to.be.false;
+    expect(stakeAccount.stakedAmount.toString()).to.be.equal(
+      parseZbcnUnits(1000).toString()
+    );
+    expect(stakeAccount.lockPeriod.toNumber()).to.be.equal(daysToSeconds(90));
+    expect(stakeAccount.rewardRate.toNumber()).to.be.equal(1200);
+    expect(stakeAccount.rewardAmount.toNumber()).to.be.equal(
+      13150684 - paidReward + newReward
+    );
+    // The matured remainder stays earned instead of vesting over the new lock.
+    expect(stakeAccount.vestedReward.toNumber()).to.be.equal(
+      13150684 - paidReward
+    );
+
+    const lockupAfter = await program.account.lockup.fetch(lockup);
+    expect(
+      lockupBefore.stakedToken.totalStaked
+        .sub(lockupAfter.stakedToken.totalStaked)
+        .toString()
+    ).to.be.equal(parseZbcnUnits(1000).toString());
+    expect(lockupAfter.stats.activePositions.toNumber()).to.be.equal(
+      lockupBefore.stats.activePositions.toNumber()
+    );
+    expect(
+      lockupAfter.stats.totalRewardLiability
+        .sub(lockupBefore.stats.totalRewardLiability)
+        .toNumber()
+    ).to.be.equal(newReward - paidReward);
+    expect(
+      lockupAfter.stats.totalRewardsPaid
+        .sub(lockupBefore.stats.totalRewardsPaid)
+        .toNumber()
+    ).to.be.equal(paidReward);
+  });
+
+  it("tops up an open position under both lock policies", async () => {
+    const nonceInfo = await getUserNonceInfo(program, stakerNonce1);
+    const nonce = nonceInfo ? nonceInfo.nonce : BigInt(0);
+    const staker1Pda = deriveStakeAddress(
+      staker1.publicKey,
+      lockup,
+      nonce,
+      program.programId
+    );
+
+    await program.methods
+      .stakeZbcn({
+        amount: new anchor.BN(parseZbcnUnits(1000)),
+        lockPeriod: new anchor.BN(daysToSeconds(30)),
+        nonce: new anchor.BN(nonce.toString()),
+      })
+      .accountsStrict({
+        staker: staker1.publicKey,
+        feePayer: feePayer.publicKey,
+        lockup: lockup,
+        stakePda: staker1Pda,
+        userNonce: stakerNonce1,
+        stakeToken: mint,
+        stakerTokenAccount: staker1Ata,
+        stakeVault: stakeVault,
+        stakeVaultTokenAccount: stakeVaultAta,
+        rewardToken: mint,
+        rewardVault,
+        rewardVaultTokenAccount: rewardVaultAta,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        receiptMint: null,
+        stakerReceiptAccount: null,
+        receiptTokenProgram: null,
+      })
+      .signers([staker1, feePayer])
+      .rpc();
+
+    const increaseStake = (amount: number) =>
+      program.methods
+        .increaseStake({
+          nonce: new anchor.BN(nonce.toString()),
+          amount: new anchor.BN(parseZbcnUnits(amount)),
+        })
+        .accountsStrict({
+          staker: staker1.publicKey,
+          lockup: lockup,
+          stakePda: staker1Pda,
+          stakeToken: mint,
+          stakerTokenAccount: staker1Ata,
+          stakeVault: stakeVault,
+          stakeVaultTokenAccount: stakeVaultAta,
+          rewardVault,
+          rewardVaultTokenAccount: rewardVaultAta,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([staker1])
+        .rpc();
+
+    // Default policy: the lock restarts and half of the 6575342 reward is kept.
+    await warpForward(context, daysToSeconds(15));
+    const lockupBefore = await program.account.lockup.fetch(lockup);
+    await increaseStake(1000);
+
+    let stakeAccount = await program.account.userStakeData.fetch(staker1Pda);
+    const resetTime = stakeAccount.createdTime.toNumber();
+    expect(stakeAccount.stakedAmount.toString()).to.be.equal(
+      parseZbcnUnits(2000).toString()
+    );
+    // 3287671 accrued + 2000 ZBCN for 30 days at 8%
+    expect(stakeAccount.rewardAmount.toNumber()).to.be.equal(
+      3287671 + 13150684
+    );
+    // The accrued part stays earned rather than vesting again.
+    expect(stakeAccount.vestedReward.toNumber()).to.be.equal(3287671);
+
+    const lockupAfter = await program.account.lockup.fetch(lockup);
+    expect(
+      lockupAfter.stakedToken.totalStaked
+        .sub(lockupBefore.stakedToken.totalStaked)
+        .toString()
+    ).to.be.equal(parseZbcnUnits(1000).toString());
+    expect(
+      lockupAfter.stats.totalRewardLiability
+        .sub(lockupBefore.stats.totalRewardLiability)
+        .toNumber()
+    ).to.be.equal(3287671 + 13150684 - 6575342);
+
+    await program.methods
+      .setLockupOptions({
+        topUpPolicy: { weightedLock: {} },
+        claimEnabled: true,
+        cooldownSeconds: new anchor.BN(0),
+        transfersEnabled: false,
+      })
+      .accountsStrict({
+        updater: provider.publicKey,
+        lockup: lockup,
+      })
+      .rpc();
+
+    // Double the 30 day rate; only the top-up earns it.
+    const updateThirtyDayRate = (reward: number) =>
+      program.methods
+        .updateLockup({
+          fee: lockUpData.fee,
+          feeVault: lockUpData.feeVault,
+          minimumStake: lockUpData.minimumStake,
+          durationMap: rewardSchemes.map((scheme) =>
+            scheme.duration.eq(new anchor.BN(daysToSeconds(30)))
+              ? { duration: scheme.duration, reward: new anchor.BN(reward) }
+              : scheme
+          ),
+        })
+        .accountsStrict({
+          updater: provider.publicKey,
+          lockup: lockup,
+        })
+        .rpc();
+    await updateThirtyDayRate(1600);
+
+    // Doubling the principal 10 days in moves the start forward by 5 days,
+    // leaving 25 days of lock on 4000 ZBCN.
+    await warpForward(context, daysToSeconds(10));
+    await increaseStake(2000);
+
+    stakeAccount = await program.account.userStakeData.fetch(staker1Pda);
+    expect(stakeAccount.createdTime.toNumber()).to.be.equal(
+      resetTime + daysToSeconds(5)
+    );
+    // 3287671 vested + 13150684 * 10 / 30 accrued, then 25 days of
+    // 2000 ZBCN at 8% and 2000 ZBCN at 16%
+    expect(stakeAccount.vestedReward.toNumber()).to.be.equal(7671232);
+    expect(stakeAccount.rewardAmount.toNumber()).to.be.equal(
+      7671232 + 10958904 + 21917808
+    );
+    expect(stakeAccount.rewardRate.toNumber()).to.be.equal(1200);
+    expect(stakeAccount.rewardStartTime.toNumber()).to.be.equal(
+      resetTime + daysToSeconds(10)
+    );
+
+    const claimRewards = () =>
+      program.methods
+        .claimRewards(new anchor.BN(nonce.toString()))
+        .accountsStrict({
+          staker: staker1.publicKey,
+          feePayer: feePayer.publicKey,
+          lockup: lockup,
+          stakePda: staker1Pda,
+          rewardToken: mint,
+          stakerRewardTokenAccount: staker1Ata,
+          rewardVault,
+          rewardVaultTokenAccount: rewardVaultAta,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([staker1, feePayer])
+        .rpc();
+
+    // Right after the top-up only the vested part is claimable; the new
+    // reward accrues over the 25 days left, not from the earlier start.
+    let balanceBefore = await getTokenAccountBalance(provider, staker1Ata);
+    await claimRewards();
+    expect(
+      (await getTokenAccountBalance(provider, staker1Ata)) - balanceBefore
+    ).to.be.equal(BigInt(7671232));
+
+    // 32876712 * 5 / 25
+    await warpForward(context, daysToSeconds(5));
+    balanceBefore = await getTokenAccountBalance(provider, staker1Ata);
+    await claimRewards();
+    expect(
+      (await getTokenAccountBalance(provider, staker1Ata)) - balanceBefore
+    ).to.be.equal(BigInt(6575342));
+
+    await updateThirtyDayRate(800);
+
+    await program.methods
+      .setLockupOptions({
+        topUpPolicy: { resetLock: {} },
+        claimEnabled: false,
+        cooldownSeconds: new anchor.BN(0),
+        transfersEnabled: false,
+      })
+      .accountsStrict({
+        updater: provider.publicKey,
+        lockup: lockup,
+      })
+      .rpc();
+  });
+
+  it("extends an open position to a longer tier", async () => {
+    const nonceInfo = await getUserNonceInfo(program, stakerNonce1);
+    const nonce = nonceInfo ? nonceInfo.nonce : BigInt(0);
+    const staker1Pda = deriveStakeAddress(
+      staker1.publicKey,
+      lockup,
+      nonce,
+      program.programId
+    );
+
+    await program.methods
+      .stakeZbcn({
+        amount: new anchor.BN(parseZbcnUnits(1000)),
+        lockPeriod: new anchor.BN(daysToSeconds(30)),
+        nonce: new anchor.BN(nonce.toString()),
+      })
+      .accountsStrict({
+        staker: staker1.publicKey,
+        feePayer: feePayer.publicKey,
+        lockup: lockup,
+        stakePda: staker1Pda,
+        userNonce: stakerNonce1,
+        stakeToken: mint,
+        stakerTokenAccount: staker1Ata,
+        stakeVault: stakeVault,
+        stakeVaultTokenAccount: stakeVaultAta,
+        rewardToken: mint,
+        rewardVault,
+        rewardVaultTokenAccount: rewardVaultAta,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        receiptMint: null,
+        stakerReceiptAccount: null,
+        receiptTokenProgram: null,
+      })
+      .signers([staker1, feePayer])
+      .rpc();
+
+    const extendLock = (days: number) =>
+      program.methods
+        .extendLock({
+          nonce: new anchor.BN(nonce.toString()),
+          lockPeriod: new anchor.BN(daysToSeconds(days)),
+        })
+        .accountsStrict({
+          staker: staker1.publicKey,
+          lockup: lockup,
+          stakePda: staker1Pda,
+          rewardVault,
+          rewardVaultTokenAccount: rewardVaultAta,
+        })
+        .signers([staker1])
+        .rpc();
+
+    await warpForward(context, daysToSeconds(10));
+
+    let error: Error | undefined;
+    try {
+      await extendLock(30);
+    } catch (err) {
+      error = err;
+    }
+    expect(error).not.to.be.undefined;
+    expect(error.message).to.include("LockNotExtended");
+
+    const lockupBefore = await program.account.lockup.fetch(lockup);
+    await extendLock(90);
+
+    // A third of the 30 day reward is carried into the new 90 day lock.
+    const rewardAmount = 2191780 + 29589041;
+    const stakeAccount = await program.account.userStakeData.fetch(staker1Pda);
+    expect(stakeAccount.lockPeriod.toNumber()).to.be.equal(daysToSeconds(90));
+    expect(stakeAccount.rewardRate.toNumber()).to.be.equal(1200);
+    expect(stakeAccount.rewardAmount.toNumber()).to.be.equal(rewardAmount);
+
+    const lockupAfter = await program.account.lockup.fetch(lockup);
+    expect(
+      lockupAfter.stats.totalRewardLiability
+        .sub(lockupBefore.stats.totalRewardLiability)
+        .toNumber()
+    ).to.be.equal(rewardAmount - 6575342);
+  });
+
+  it("merges positions sharing a lock period", async () => {
+    const nonceInfo = await getUserNonceInfo(program, stakerNonce1);
+    const firstNonce = nonceInfo ? nonceInfo.nonce : BigInt(0);
+    const positions: PublicKey[] = [];
+
+    for (const nonce of [firstNonce, firstNonce + BigInt(1)]) {
+      const stakePda = deriveStakeAddress(
+        staker1.publicKey,
+        lockup,
+        nonce,
+        program.programId
+      );
+      positions.push(stakePda);
+
+      await program.methods
+        .stakeZbcn({
+          amount: new anchor.BN(parseZbcnUnits(1000)),
+          lockPeriod: new anchor.BN(daysToSeconds(30)),
+          nonce: new anchor.BN(nonce.toString()),
+        })
+        .accountsStrict({
+          staker: staker1.publicKey,
+          feePayer: feePayer.publicKey,
+          lockup: lockup,
+          stakePda,
+          userNonce: stakerNonce1,
+          stakeToken: mint,
+          stakerTokenAccount: staker1Ata,
+          stakeVault: stakeVault,
+          stakeVaultTokenAccount: stakeVaultAta,
+          rewardToken: mint,
+          rewardVault,
+          rewardVaultTokenAccount: rewardVaultAta,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          receiptMint: null,
+          stakerReceiptAccount: null,
+          receiptTokenProgram: null,
+        })
+        .signers([staker1, feePayer])
+        .rpc();
+
+      await warpForward(context, daysToSeconds(5));
+    }
+
+    const [target, source] = positions;
+    const sourceBefore = await program.account.userStakeData.fetch(source);
+    const lockupBefore = await program.account.lockup.fetch(lockup);
+
+    await program.methods
+      .mergePositions(new anchor.BN(firstNonce.toString()))
+      .accountsStrict({
+        staker: staker1.publicKey,
+        lockup: lockup,
+        stakePda: target,
+      })
+      .remainingAccounts([
+        { pubkey: source, isSigner: false, isWritable: true },
+        { pubkey: feePayer.publicKey, isSigner: false, isWritable: true },
+      ])
+      .signers([staker1])
+      .rpc();
+
+    const merged = await program.account.userStakeData.fetch(target);
+    expect(merged.stakedAmount.toString()).to.be.equal(
+      parseZbcnUnits(2000).toString()
+    );
+    // Maturity follows the later of the two positions.
+    expect(merged.createdTime.toString()).to.be.equal(
+      sourceBefore.createdTime.toString()
+    );
+    expect(merged.rewardRate.toNumber()).to.be.equal(800);
+    expect(merged.rewardAmount.toNumber()).to.be.equal(2 * 6575342);
+    expect(await client.getAccount(source)).to.be.null;
+
+    const lockupAfter = await program.account.lockup.fetch(lockup);
+    expect(lockupAfter.stats.activePositions.toNumber()).to.be.equal(
+      lockupBefore.stats.activePositions.toNumber() - 1
+    );
+    expect(lockupAfter.stats.totalRewardLiability.toString()).to.be.equal(
+      lockupBefore.stats.totalRewardLiability.toString()
+    );
+  });
+
+  it("closes claimed positions and refunds the rent payer", async () => {
+    const nonceInfo = await getUserNonceInfo(program, stakerNonce1);
+    const firstNonce = nonceInfo ? nonceInfo.nonce : BigInt(0);
+    const positions: PublicKey[] = [];
+
+    for (const nonce of [firstNonce, firstNonce + BigInt(1)]) {
+      const stakePda = deriveStakeAddress(
+        staker1.publicKey,
+        lockup,
+        nonce,
+        program.programId
+      );
+      positions.push(stakePda);
+
+      await program.methods
+        .stakeZbcn({
+          amount: new anchor.BN(parseZbcnUnits(1000)),
+          lockPeriod: new anchor.BN(daysToSeconds(30)),
+          nonce: new anchor.BN(nonce.toString()),
+        })
+        .accountsStrict({
+          staker: staker1.publicKey,
+          feePayer: feePayer.publicKey,
+          lockup: lockup,
+          stakePda,
+          userNonce: stakerNonce1,
+          stakeToken: mint,
+          stakerTokenAccount: staker1Ata,
+          stakeVault: stakeVault,
+          stakeVaultTokenAccount: stakeVaultAta,
+          rewardToken: mint,
+          rewardVault,
+          rewardVaultTokenAccount: rewardVaultAta,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          receiptMint: null,
+          stakerReceiptAccount: null,
+          receiptTokenProgram: null,
+        })
+        .signers([staker1, feePayer])
+        .rpc();
+    }
+
+    const stakeAccount = await program.account.userStakeData.fetch(
+      positions[0]
+    );
+    expect(stakeAccount.rentPayer.toBase58()).to.be.equal(
+      feePayer.publicKey.toBase58()
+    );
+
+    const closePosition = (nonce: bigint, stakePda: PublicKey) =>
+      program.methods
+        .closePosition(new anchor.BN(nonce.toString()))
+        .accountsStrict({
+          staker: staker1.publicKey,
+          lockup: lockup,
+          stakePda,
+          rentPayer: feePayer.publicKey,
+        })
+        .signers([staker1])
+        .rpc();
+
+    let error: Error | undefined;
+    try {
+      await closePosition(firstNonce, positions[0]);
+    } catch (err) {
+      error = err;
+    }
+    expect(error).not.to.be.undefined;
+    expect(error.message).to.include("PositionNotClaimed");
+
+    await warpForward(context, daysToSeconds(31));
+
+    const unstake = (nonce: bigint, stakePda: PublicKey, close: boolean) =>
+      program.methods
+        .unstakeZbcn(new anchor.BN(nonce.toString()))
+        .accountsStrict({
+          rewardToken: mint,
+          feePayer: feePayer.publicKey,
+          rewardVault,
+          rewardVaultTokenAccount: rewardVaultAta,
+          stakerRewardTokenAccount: staker1Ata,
+          staker: program.provider.publicKey,
+          beneficiary: program.provider.publicKey,
+          lockup: lockup,
+          stakePda,
+          stakeToken: mint,
+          stakerTokenAccount: staker1Ata,
+          stakeVault: stakeVault,
+          stakeVaultTokenAccount: stakeVaultAta,
+          feeVault: feeVault,
+          feeVaultTokenAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          rentPayer: close ? feePayer.publicKey : null,
+          receiptMint: null,
+          stakerReceiptAccount: null,
+          receiptTokenProgram: null,
+        })
+        .signers([staker1, feePayer])
+        .rpc();
+
+    // Unstake and close in one go.
+    let payerBalance = (await client.getAccount(feePayer.publicKey)).lamports;
+    const rent = (await client.getAccount(positions[0])).lamports;
+    await unstake(firstNonce, positions[0], true);
+    expect(await client.getAccount(positions[0])).to.be.null;
+    expect(
+      (await client.getAccount(feePayer.publicKey)).lamports
+    ).to.be.equal(payerBalance + rent);
+
+    // Unstake, then close separately.
+    const secondNonce = firstNonce + BigInt(1);
+    await unstake(secondNonce, positions[1], false);
+    expect(await client.getAccount(positions[1])).not.to.be.null;
+
+    payerBalance = (await client.getAccount(feePayer.publicKey)).lamports;
+    await closePosition(secondNonce, positions[1]);
+    expect(await client.getAccount(positions[1])).to.be.null;
+    expect(
+      (await client.getAccount(feePayer.publicKey)).lamports
+    ).to.be.equal(payerBalance + rent);
+  });
+
+  it("exits early by forfeiting reward and paying the penalty", async () => {
+    const setPenaltySchedule = (penalties: any[]) =>
+      program.methods
+        .setPenaltySchedule({ destination: { feeVault: {} }, penalties })
+        .accountsStrict({
+          updater: provider.publicKey,
+          lockup: lockup,
+        })
+        .rpc();
+
+    // 10% penalty on the 30 day tier
+    await setPenaltySchedule([
+      {
+        duration: new anchor.BN(daysToSeconds(30)),
+        penalty: new anchor.BN(100),
+      },
+    ]);
+
+    const nonceInfo = await getUserNonceInfo(program, stakerNonce1);
+    const nonce = nonceInfo ? nonceInfo.nonce : BigInt(0);
+    const staker1Pda = deriveStakeAddress(
+      staker1.publicKey,
+      lockup,
+      nonce,
+      program.programId
+    );
+
+    await program.methods
+      .stakeZbcn({
+        amount: new anchor.BN(parseZbcnUnits(1000)),
+        lockPeriod: new anchor.BN(daysToSeconds(30)),
+        nonce: new anchor.BN(nonce.toString()),
+      })
+      .accountsStrict({
+        staker: staker1.publicKey,
+        feePayer: feePayer.publicKey,
+        lockup: lockup,
+        stakePda: staker1Pda,
+        userNonce: stakerNonce1,
+        stakeToken: mint,
+        stakerTokenAccount: staker1Ata,
+        stakeVault: stakeVault,
+        stakeVaultTokenAccount: stakeVaultAta,
+        rewardToken: mint,
+        rewardVault,
+        rewardVaultTokenAccount: rewardVaultAta,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        receiptMint: null,
+        stakerReceiptAccount: null,
+        receiptTokenProgram: null,
+      })
+      .signers([staker1, feePayer])
+      .rpc();
+
+    let stakeAccount = await program.account.userStakeData.fetch(staker1Pda);
+    expect(stakeAccount.penalty.toNumber()).to.be.equal(100);
+
+    // Raising the penalty later does not touch the open position.
+    await setPenaltySchedule([
+      {
+        duration: new anchor.BN(daysToSeconds(30)),
+        penalty: new anchor.BN(500),
+      },
+    ]);
+
+    await warpForward(context, daysToSeconds(10));
+
+    const lockupBefore = await program.account.lockup.fetch(lockup);
+    const stakerBalanceBefore = await getTokenAccountBalance(
+      provider,
+      staker1Ata
+    );
+    const feeVaultBalanceBefore = await getTokenAccountBalance(
+      provider,
+      feeVaultTokenAccount
+    );
+
+    await program.methods
+      .earlyUnstake(new anchor.BN(nonce.toString()))
+      .accountsStrict({
+        staker: staker1.publicKey,
+        feePayer: feePayer.publicKey,
+        lockup: lockup,
+        stakePda: staker1Pda,
+        stakeToken: mint,
+        stakerTokenAccount: staker1Ata,
+        stakeVault: stakeVault,
+        stakeVaultTokenAccount: stakeVaultAta,
+        rewardVault,
+        rewardVaultTokenAccount: rewardVaultAta,
+        feeVaultTokenAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([staker1, feePayer])
+      .rpc();
+
+    const penalty = BigInt(parseZbcnUnits(100));
+    expect(
+      (await getTokenAccountBalance(provider, staker1Ata)) - stakerBalanceBefore
+    ).to.be.equal(BigInt(parseZbcnUnits(1000)) - penalty);
+    expect(
+      (await getTokenAccountBalance(provider, feeVaultTokenAccount)) -
+        feeVaultBalanceBefore
+    ).to.be.equal(penalty);
+
+    stakeAccount = await program.account.userStakeData.fetch(staker1Pda);
+    expect(stakeAccount.stakeClaimed).to.be.true;
+    expect(stakeAccount.rewardAmount.toNumber()).to.be.equal(0);
+
+    const lockupAfter = await program.account.lockup.fetch(lockup);
+    expect(
+      lockupBefore.stats.totalRewardLiability
+        .sub(lockupAfter.stats.totalRewardLiability)
+        .toNumber()
+    ).to.be.equal(6575342);
+    expect(
+      lockupAfter.stats.totalPenaltiesCollected
+        .sub(lockupBefore.stats.totalPenaltiesCollected)
+        .toString()
+    ).to.be.equal(penalty.toString());
+    expect(lockupAfter.stats.totalRewardsPaid.toString()).to.be.equal(
+      lockupBefore.stats.totalRewardsPaid.toString()
+    );
+
+    await setPenaltySchedule([]);
+  });
+
+  it("rolls a matured auto-renew position into a new lock", async () => {
+    const nonceInfo = await getUserNonceInfo(program, stakerNonce1);
+    const nonce = nonceInfo ? nonceInfo.nonce : BigInt(0);
+    const staker1Pda = deriveStakeAddress(
+      staker1.publicKey,
+      lockup,
+      nonce,
+      program.programId
+    );
+
+    await program.methods
+      .stakeZbcn({
+        amount: new anchor.BN(parseZbcnUnits(1000)),
+        lockPeriod: new anchor.BN(daysToSeconds(30)),
+        nonce: new anchor.BN(nonce.toString()),
+      })
+      .accountsStrict({
+        staker: staker1.publicKey,
+        feePayer: feePayer.publicKey,
+        lockup: lockup,
+        stakePda: staker1Pda,
+        userNonce: stakerNonce1,
+        stakeToken: mint,
+        stakerTokenAccount: staker1Ata,
+        stakeVault: stakeVault,
+        stakeVaultTokenAccount: stakeVaultAta,
+        rewardToken: mint,
+        rewardVault,
+        rewardVaultTokenAccount: rewardVaultAta,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        receiptMint: null,
+        stakerReceiptAccount: null,
+        receiptTokenProgram: null,
+      })
+      .signers([staker1, feePayer])
+      .rpc();
+
+    const setAutoRenew = (autoRenew: boolean, compoundOnRenew: boolean) =>
+      program.methods
+        .setAutoRenew({
+          nonce: new anchor.BN(nonce.toString()),
+          autoRenew,
+          compoundOnRenew,
+        })
+        .accountsStrict({
+          staker: staker1.publicKey,
+          lockup: lockup,
+          stakePda: staker1Pda,
+        })
+        .signers([staker1])
+        .rpc();
+
+    const crankRenew = () =>
+      program.methods
+        .crankRenew(new anchor.BN(nonce.toString()))
+        .accountsStrict({
+          cranker: feePayer.publicKey,
+          lockup: lockup,
+          staker: staker1.publicKey,
+          stakePda: staker1Pda,
+          stakeVault: stakeVault,
+          stakeVaultTokenAccount: stakeVaultAta,
+          rewardVault,
+          rewardVaultTokenAccount: rewardVaultAta,
+          stakerRewardTokenAccount: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([feePayer])
+        .rpc();
+
+    await setAutoRenew(true, true);
+
+    let error: Error | undefined;
+    try {
+      await crankRenew();
+    } catch (err) {
+      error = err;
+    }
+    expect(error).not.to.be.undefined;
+    expect(error.message).to.include("StakeRewardNotClaimable");
+
+    await warpForward(context, daysToSeconds(31));
+
+    const stakeBefore = await program.account.userStakeData.fetch(staker1Pda);
+    const lockupBefore = await program.account.lockup.fetch(lockup);
+    await crankRenew();
+
+    // The 6575342 reward is compounded and the new lock starts at maturity.
+    const stakeAfter = await program.account.userStakeData.fetch(staker1Pda);
+    expect(stakeAfter.stakedAmount.toNumber()).to.be.equal(
+      parseZbcnUnits(1000) + 6575342
+    );
+    expect(stakeAfter.createdTime.toNumber()).to.be.equal(
+      stakeBefore.createdTime.toNumber() + daysToSeconds(30)
+    );
+    expect(stakeAfter.rewardAmount.toNumber()).to.be.equal(6618577);
+    expect(stakeAfter.stakeClaimed).to.be.false;
+
+    const lockupAfter = await program.account.lockup.fetch(lockup);
+    expect(
+      lockupAfter.stakedToken.totalStaked
+        .sub(lockupBefore.stakedToken.totalStaked)
+        .toNumber()
+    ).to.be.equal(6575342);
+    expect(
+      lockupAfter.stats.totalRewardsPaid
+        .sub(lockupBefore.stats.totalRewardsPaid)
+        .toNumber()
+    ).to.be.equal(6575342);
+    expect(
+      lockupAfter.stats.totalRewardLiability
+        .sub(lockupBefore.stats.totalRewardLiability)
+        .toNumber()
+    ).to.be.equal(6618577 - 6575342);
+
+    // Once the staker opts out the crank can no longer renew the position.
+    await setAutoRenew(false, false);
+    await warpForward(context, daysToSeconds(30));
+
+    error = undefined;
+    try {
+      await crankRenew();
+    } catch (err) {
+      error = err;
+    }
+    expect(error).not.to.be.undefined;
+    expect(error.message).to.include("AutoRenewDisabled");
+  });
+
+  it("compounds a matured position into a new one", async () => {
+    const nonceInfo = await getUserNonceInfo(program, stakerNonce1);
+    const nonce = nonceInfo ? nonceInfo.nonce : BigInt(0);
+    const newNonce = nonce + BigInt(1);
+    const staker1Pda = deriveStakeAddress(
+      staker1.publicKey,
+      lockup,
+      nonce,
+      program.programId
+    );
+    const newStakePda = deriveStakeAddress(
+      staker1.publicKey,
+      lockup,
+      newNonce,
+      program.programId
+    );
+
+    await program.methods
+      .stakeZbcn({
+        amount: new anchor.BN(parseZbcnUnits(1000)),
+        lockPeriod: new anchor.BN(daysToSeconds(30)),
+        nonce: new anchor.BN(nonce.toString()),
+      })
+      .accountsStrict({
+        staker: staker1.publicKey,
+        feePayer: feePayer.publicKey,
+        lockup: lockup,
+        stakePda: staker1Pda,
+        userNonce: stakerNonce1,
+        stakeToken: mint,
+        stakerTokenAccount: staker1Ata,
+        stakeVault: stakeVault,
+        stakeVaultTokenAccount: stakeVaultAta,
+        rewardToken: mint,
+        rewardVault,
+        rewardVaultTokenAccount: rewardVaultAta,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        receiptMint: null,
+        stakerReceiptAccount: null,
+        receiptTokenProgram: null,
+      })
+      .signers([staker1, feePayer])
+      .rpc();
+
+    await warpForward(context, daysToSeconds(31));
+
+    const lockupBefore = await program.account.lockup.fetch(lockup);
+    const stakeVaultBefore = await getTokenAccountBalance(
+      provider,
+      stakeVaultAta
+    );
+
+    await program.methods
+      .compound({
+        nonce: new anchor.BN(nonce.toString()),
+        newNonce: new anchor.BN(newNonce.toString()),
+        lockPeriod: new anchor.BN(daysToSeconds(90)),
+      })
+      .accountsStrict({
+        staker: staker1.publicKey,
+        feePayer: feePayer.publicKey,
+        lockup: lockup,
+        stakePda: staker1Pda,
+        userNonce: stakerNonce1,
+        newStakePda,
+        stakeVault: stakeVault,
+        stakeVaultTokenAccount: stakeVaultAta,
+        rewardVault,
+        rewardVaultTokenAccount: rewardVaultAta,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([staker1, feePayer])
+      .rpc();
+
+    const oldPosition = await program.account.userStakeData.fetch(staker1Pda);
+    expect(oldPosition.stakeClaimed).to.be.true;
+
+    const newPosition = await program.account.userStakeData.fetch(newStakePda);
+    expect(newPosition.stakedAmount.toNumber()).to.be.equal(
+      parseZbcnUnits(1000) + 6575342
+    );
+    expect(newPosition.lockPeriod.toNumber()).to.be.equal(daysToSeconds(90));
+    expect(newPosition.rewardAmount.toNumber()).to.be.equal(29783599);
+
+    expect(
+      (await getTokenAccountBalance(provider, stakeVaultAta)) - stakeVaultBefore
+    ).to.be.equal(BigInt(6575342));
+
+    const lockupAfter = await program.account.lockup.fetch(lockup);
+    expect(lockupAfter.stats.activePositions.toNumber()).to.be.equal(
+      lockupBefore.stats.activePositions.toNumber()
+    );
+    expect(
+      lockupAfter.stakedToken.totalStaked
+        .sub(lockupBefore.stakedToken.totalStaked)
+        .toNumber()
+    ).to.be.equal(6575342);
+    expect(
+      lockupAfter.stats.totalRewardLiability
+        .sub(lockupBefore.stats.totalRewardLiability)
+        .toNumber()
+    ).to.be.equal(29783599 - 6575342);
+  });
+
+  it("claims accrued reward before unstaking", async () => {
+    const setClaimEnabled = (claimEnabled: boolean) =>
+      program.methods
+        .setLockupOptions({
+          topUpPolicy: { resetLock: {} },
+          claimEnabled,
+          cooldownSeconds: new anchor.BN(0),
+          transfersEnabled: false,
+        })
+        .accountsStrict({
+          updater: provider.publicKey,
+          lockup: lockup,
+        })
+        .rpc();
+
+    const nonceInfo = await getUserNonceInfo(program, stakerNonce1);
+    const nonce = nonceInfo ? nonceInfo.nonce : BigInt(0);
+    const staker1Pda = deriveStakeAddress(
+      staker1.publicKey,
+      lockup,
+      nonce,
+      program.programId
+    );
+
+    await program.methods
+      .stakeZbcn({
+        amount: new anchor.BN(parseZbcnUnits(1000)),
+        lockPeriod: new anchor.BN(daysToSeconds(30)),
+        nonce: new anchor.BN(nonce.toString()),
+      })
+      .accountsStrict({
+        staker: staker1.publicKey,
+        feePayer: feePayer.publicKey,
+        lockup: lockup,
+        stakePda: staker1Pda,
+        userNonce: stakerNonce1,
+        stakeToken: mint,
+        stakerTokenAccount: staker1Ata,
+        stakeVault: stakeVault,
+        stakeVaultTokenAccount: stakeVaultAta,
+        rewardToken: mint,
+        rewardVault,
+        rewardVaultTokenAccount: rewardVaultAta,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        receiptMint: null,
+        stakerReceiptAccount: null,
+        receiptTokenProgram: null,
+      })
+      .signers([staker1, feePayer])
+      .rpc();
+
+    await warpForward(context, daysToSeconds(10));
+
+    const claimRewards = () =>
+      program.methods
+        .claimRewards(new anchor.BN(nonce.toString()))
+        .accountsStrict({
+          staker: staker1.publicKey,
+          feePayer: feePayer.publicKey,
+          lockup: lockup,
+          stakePda: staker1Pda,
+          rewardToken: mint,
+          stakerRewardTokenAccount: staker1Ata,
+          rewardVault,
+          rewardVaultTokenAccount: rewardVaultAta,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([staker1, feePayer])
+        .rpc();
+
+    let error: Error | undefined;
+    try {
+      await claimRewards();
+    } catch (err) {
+      error = err;
+    }
+    expect(error).not.to.be.undefined;
+    expect(error.message).to.include("ClaimDisabled");
+
+    await setClaimEnabled(true);
+
+    // A third of the way through the lock a third of the reward is claimable.
+    let balanceBefore = await getTokenAccountBalance(provider, staker1Ata);
+    await claimRewards();
+    expect(
+      (await getTokenAccountBalance(provider, staker1Ata)) - balanceBefore
+    ).to.be.equal(BigInt(2191780));
+
+    const stakeAccount = await program.account.userStakeData.fetch(staker1Pda);
+    expect(stakeAccount.rewardClaimedSoFar.toNumber()).to.be.equal(2191780);
+
+    await warpForward(context, daysToSeconds(21));
+
+    // Unstaking pays principal and only the unclaimed remainder.
+    const lockupBefore = await program.account.lockup.fetch(lockup);
+    balanceBefore = await getTokenAccountBalance(provider, staker1Ata);
+    await program.methods
+      .unstakeZbcn(new anchor.BN(nonce.toString()))
+      .accountsStrict({
+        rewardToken: mint,
+        feePayer: feePayer.publicKey,
+        rewardVault,
+        rewardVaultTokenAccount: rewardVaultAta,
+        stakerRewardTokenAccount: staker1Ata,
+        staker: program.provider.publicKey,
+        beneficiary: program.provider.publicKey,
+        lockup: lockup,
+        stakePda: staker1Pda,
+        stakeToken: mint,
+        stakerTokenAccount: staker1Ata,
+        stakeVault: stakeVault,
+        stakeVaultTokenAccount: stakeVaultAta,
+        feeVault: feeVault,
+        feeVaultTokenAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        rentPayer: null,
+        receiptMint: null,
+        stakerReceiptAccount: null,
+        receiptTokenProgram: null,
+      })
+      .signers([staker1, feePayer])
+      .rpc();
+
+    expect(
+      (await getTokenAccountBalance(provider, staker1Ata)) - balanceBefore
+    ).to.be.equal(BigInt(parseZbcnUnits(1000) + 6575342 - 2191780));
+
+    const lockupAfter = await program.account.lockup.fetch(lockup);
+    expect(
+      lockupBefore.stats.totalRewardLiability
+        .sub(lockupAfter.stats.totalRewardLiability)
+        .toNumber()
+    ).to.be.equal(6575342 - 2191780);
+
+    await setClaimEnabled(false);
+  });
+
+  it("withdraws principal only after the cooldown", async () => {
+    const setCooldown = (cooldownSeconds: number) =>
+      program.methods
+        .setLockupOptions({
+          topUpPolicy: { resetLock: {} },
+          claimEnabled: false,
+          cooldownSeconds: new anchor.BN(cooldownSeconds),
+          transfersEnabled: false,
+        })
+        .accountsStrict({
+          updater: provider.publicKey,
+          lockup: lockup,
+        })
+        .rpc();
+
+    await setCooldown(daysToSeconds(1));
+
+    const nonceInfo = await getUserNonceInfo(program, stakerNonce1);
+    const nonce = nonceInfo ? nonceInfo.nonce : BigInt(0);
+    const staker1Pda = deriveStakeAddress(
+      staker1.publicKey,
+      lockup,
+      nonce,
+      program.programId
+    );
+
+    await program.methods
+      .stakeZbcn({
+        amount: new anchor.BN(parseZbcnUnits(1000)),
+        lockPeriod: new anchor.BN(daysToSeconds(30)),
+        nonce: new anchor.BN(nonce.toString()),
+      })
+      .accountsStrict({
+        staker: staker1.publicKey,
+        feePayer: feePayer.publicKey,
+        lockup: lockup,
+        stakePda: staker1Pda,
+        userNonce: stakerNonce1,
+        stakeToken: mint,
+        stakerTokenAccount: staker1Ata,
+        stakeVault: stakeVault,
+        stakeVaultTokenAccount: stakeVaultAta,
+        rewardToken: mint,
+        rewardVault,
+        rewardVaultTokenAccount: rewardVaultAta,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        receiptMint: null,
+        stakerReceiptAccount: null,
+        receiptTokenProgram: null,
+      })
+      .signers([staker1, feePayer])
+      .rpc();
+
+    // The cooldown is fixed at stake time; later option changes do not reach
+    // the open position.
+    await setCooldown(daysToSeconds(10));
+    expect(
+      (
+        await program.account.userStakeData.fetch(staker1Pda)
+      ).cooldownSeconds.toNumber()
+    ).to.be.equal(daysToSeconds(1));
+
+    let error: Error | undefined;
+    try {
+      await setCooldown(daysToSeconds(31));
+    } catch (err) {
+      error = err;
+    }
+    expect(error).not.to.be.undefined;
+    expect(error.message).to.include("InvalidCooldown");
+
+    await warpForward(context, daysToSeconds(31));
+
+    error = undefined;
+    try {
+      await program.methods
+        .unstakeZbcn(new anchor.BN(nonce.toString()))
+        .accountsStrict({
+          rewardToken: mint,
+          feePayer: feePayer.publicKey,
+          rewardVault,
+          rewardVaultTokenAccount: rewardVaultAta,
+          stakerRewardTokenAccount: staker1Ata,
+          staker: staker1.publicKey,
+          beneficiary: staker1.publicKey,
+          lockup: lockup,
+          stakePda: staker1Pda,
+          stakeToken: mint,
+          stakerTokenAccount: staker1Ata,
+          stakeVault: stakeVault,
+          stakeVaultTokenAccount: stakeVaultAta,
+          feeVault: feeVault,
+          feeVaultTokenAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          rentPayer: null,
+          receiptMint: null,
+          stakerReceiptAccount: null,
+          receiptTokenProgram: null,
+        })
+        .signers([staker1, feePayer])
+        .rpc();
+    } catch (err) {
+      error = err;
+    }
+    expect(error).not.to.be.undefined;
+    expect(error.message).to.include("CooldownRequired");
+
+    // A partial exit cannot skip the cooldown either.
+    error = undefined;
+    try {
+      await program.methods
+        .partialUnstake({
+          nonce: new anchor.BN(nonce.toString()),
+          amount: new anchor.BN(parseZbcnUnits(400)),
+          lockPeriod: new anchor.BN(daysToSeconds(30)),
+        })
+        .accountsStrict({
+          staker: staker1.publicKey,
+          feePayer: feePayer.publicKey,
+          lockup: lockup,
+          stakePda: staker1Pda,
+          rewardToken: mint,
+          stakeToken: mint,
+          stakerTokenAccount: staker1Ata,
+          stakerRewardTokenAccount: staker1Ata,
+          stakeVault: stakeVault,
+          rewardVault,
+          stakeVaultTokenAccount: stakeVaultAta,
+          rewardVaultTokenAccount: rewardVaultAta,
+          feeVaultTokenAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([staker1, feePayer])
+        .rpc();
+    } catch (err) {
+      error = err;
+    }
+    expect(error).not.to.be.undefined;
+    expect(error.message).to.include("CooldownRequired");
+
+    // Requesting the unstake pays the full reward and starts the cooldown.
+    let balanceBefore = await getTokenAccountBalance(provider, staker1Ata);
+    await program.methods
+      .requestUnstake(new anchor.BN(nonce.toString()))
+      .accountsStrict({
+        staker: staker1.publicKey,
+        beneficiary: staker1.publicKey,
+        feePayer: feePayer.publicKey,
+        lockup: lockup,
+        stakePda: staker1Pda,
+        rewardToken: mint,
+        stakerRewardTokenAccount: staker1Ata,
+        rewardVault,
+        rewardVaultTokenAccount: rewardVaultAta,
+        receiptMint: null,
+        stakerReceiptAccount: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([staker1, feePayer])
+      .rpc();
+    expect(
+      (await getTokenAccountBalance(provider, staker1Ata)) - balanceBefore
+    ).to.be.equal(BigInt(6575342));
+
+    const withdraw = () =>
+      program.methods
+        .withdraw(new anchor.BN(nonce.toString()))
+        .accountsStrict({
+          staker: staker1.publicKey,
+          beneficiary: staker1.publicKey,
+          feePayer: feePayer.publicKey,
+          lockup: lockup,
+          stakePda: staker1Pda,
+          stakeToken: mint,
+          stakerTokenAccount: staker1Ata,
+          stakeVault: stakeVault,
+          stakeVaultTokenAccount: stakeVaultAta,
+          feeVaultTokenAccount,
+          rentPayer: null,
+          receiptMint: null,
+          stakerReceiptAccount: null,
+          receiptTokenProgram: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([staker1, feePayer])
+        .rpc();
+
+    error = undefined;
+    try {
+      await withdraw();
+    } catch (err) {
+      error = err;
+    }
+    expect(error).not.to.be.undefined;
+    expect(error.message).to.include("CooldownNotElapsed");
+
+    await warpForward(context, daysToSeconds(1));
+
+    balanceBefore = await getTokenAccountBalance(provider, staker1Ata);
+    await withdraw();
+    expect(
+      (await getTokenAccountBalance(provider, staker1Ata)) - balanceBefore
+    ).to.be.equal(BigInt(parseZbcnUnits(1000)));
+
+    const stakeAccount = await program.account.userStakeData.fetch(staker1Pda);
+    expect(stakeAccount.stakeClaimed).to.be.true;
+
+    await setCooldown(0);
+  });
+
+  it("transfers a position to another wallet", async () => {
+    const setTransfersEnabled = (transfersEnabled: boolean) =>
+      program.methods
+        .setLockupOptions({
+          topUpPolicy: { resetLock: {} },
+          claimEnabled: false,
+          cooldownSeconds: new anchor.BN(0),
+          transfersEnabled,
+        })
+        .accountsStrict({
+          updater: provider.publicKey,
+          lockup: lockup,
+        })
+        .rpc();
+
+    const nonceInfo = await getUserNonceInfo(program, stakerNonce1);
+    const nonce = nonceInfo ? nonceInfo.nonce : BigInt(0);
+    const staker1Pda = deriveStakeAddress(
+      staker1.publicKey,
+      lockup,
+      nonce,
+      program.programId
+    );
+
+    await program.methods
+      .stakeZbcn({
+        amount: new anchor.BN(parseZbcnUnits(1000)),
+        lockPeriod: new anchor.BN(daysToSeconds(30)),
+        nonce: new anchor.BN(nonce.toString()),
+      })
+      .accountsStrict({
+        staker: staker1.publicKey,
+        feePayer: feePayer.publicKey,
+        lockup: lockup,
+        stakePda: staker1Pda,
+        userNonce: stakerNonce1,
+        stakeToken: mint,
+        stakerTokenAccount: staker1Ata,
+        stakeVault: stakeVault,
+        stakeVaultTokenAccount: stakeVaultAta,
+        rewardToken: mint,
+        rewardVault,
+        rewardVaultTokenAccount: rewardVaultAta,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        receiptMint: null,
+        stakerReceiptAccount: null,
+        receiptTokenProgram: null,
+      })
+      .signers([staker1, feePayer])
+      .rpc();
+    const original = await program.account.userStakeData.fetch(staker1Pda);
+
+    const recipient = Keypair.generate();
+    const recipientNonce = deriveUserNonceAddress(
+      recipient.publicKey,
+      lockup,
+      program.programId
+    );
+    const recipientPda = deriveStakeAddress(
+      recipient.publicKey,
+      lockup,
+      BigInt(0),
+      program.programId
+    );
+    const transferPosition = () =>
+      program.methods
+        .transferPosition({
+          nonce: new anchor.BN(nonce.toString()),
+          newNonce: new anchor.BN(0),
+        })
+        .accountsStrict({
+          staker: staker1.publicKey,
+          feePayer: feePayer.publicKey,
+          recipient: recipient.publicKey,
+          lockup: lockup,
+          stakePda: staker1Pda,
+          rentPayer: feePayer.publicKey,
+          recipientNonce,
+          newStakePda: recipientPda,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([staker1, feePayer])
+        .rpc();
+
+    let error: Error | undefined;
+    try {
+      await transferPosition();
+    } catch (err) {
+      error = err;
+    }
+    expect(error).not.to.be.undefined;
+    expect(error.message).to.include("TransfersDisabled");
+
+    await setTransfersEnabled(true);
+    await transferPosition();
+
+    expect(await program.account.userStakeData.fetchNullable(staker1Pda)).to.be
+      .null;
+
+    const transferred = await program.account.userStakeData.fetch(recipientPda);
+    expect(transferred.staker.toBase58()).to.be.equal(
+      recipient.publicKey.toBase58()
+    );
+    expect(transferred.nonce.toNumber()).to.be.equal(0);
+    expect(transferred.stakedAmount.toNumber()).to.be.equal(
+      original.stakedAmount.toNumber()
+    );
+    expect(transferred.rewardAmount.toNumber()).to.be.equal(
+      original.rewardAmount.toNumber()
+    );
+    expect(transferred.createdTime.toNumber()).to.be.equal(
+      original.createdTime.toNumber()
+    );
+
+    const recipientNonceInfo = await getUserNonceInfo(program, recipientNonce);
+    expect(recipientNonceInfo.nonce).to.be.equal(BigInt(1));
+
+    await setTransfersEnabled(false);
+  });
+
+  it("lets the receipt holder unstake a tokenized position", async () => {
+    const nonceInfo = await getUserNonceInfo(program, stakerNonce1);
+    const nonce = nonceInfo ? nonceInfo.nonce : BigInt(0);
+    const staker1Pda = deriveStakeAddress(
+      staker1.publicKey,
+      lockup,
+      nonce,
+      program.programId
+    );
+    const receiptMint = deriveReceiptMintAddress(staker1Pda, program.programId);
+    const staker1ReceiptAta = await getAssociatedTokenAddress(
+      receiptMint,
+      staker1.publicKey,
+      false,
+      TOKEN_2022_PROGRAM_ID
+    );
+
+    await program.methods
+      .stakeZbcn({
+        amount: new anchor.BN(parseZbcnUnits(1000)),
+        lockPeriod: new anchor.BN(daysToSeconds(30)),
+        nonce: new anchor.BN(nonce.toString()),
+      })
+      .accountsStrict({
+        staker: staker1.publicKey,
+        feePayer: feePayer.publicKey,
+        lockup: lockup,
+        stakePda: staker1Pda,
+        userNonce: stakerNonce1,
+        stakeToken: mint,
+        stakerTokenAccount: staker1Ata,
+        stakeVault: stakeVault,
+        stakeVaultTokenAccount: stakeVaultAta,
+        rewardToken: mint,
+        rewardVault,
+        rewardVaultTokenAccount: rewardVaultAta,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        receiptMint,
+        stakerReceiptAccount: staker1ReceiptAta,
+        receiptTokenProgram: TOKEN_2022_PROGRAM_ID,
+      })
+      .signers([staker1, feePayer])
+      .rpc();
+
+    const stakeAccount = await program.account.userStakeData.fetch(staker1Pda);
+    expect(stakeAccount.receiptMint.toBase58()).to.be.equal(
+      receiptMint.toBase58()
+    );
+    let receipt = await getMint(
+      provider.connection,
+      receiptMint,
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    );
+    expect(receipt.supply).to.be.equal(BigInt(1));
+    expect(receipt.decimals).to.be.equal(0);
+    expect(receipt.mintAuthority).to.be.null;
+    expect(receipt.freezeAuthority).to.be.null;
+    const metadata = await getTokenMetadata(provider.connection, receiptMint);
+    expect(metadata.name).to.be.equal(`${LOCKUP_NAME} #${nonce}`);
+    expect(metadata.symbol).to.be.equal("ZSTAKE");
+
+    // Hand the receipt to another wallet.
+    const holder = Keypair.generate();
+    const holderReceiptAta = await transferReceipt(
+      provider,
+      receiptMint,
+      staker1ReceiptAta,
+      holder.publicKey
+    );
+
+    await warpForward(context, daysToSeconds(31));
+
+    const unstake = (
+      signer: Keypair,
+      stakerTokenAccount: PublicKey,
+      stakerReceiptAccount: PublicKey | null
+    ) =>
+      program.methods
+        .unstakeZbcn(new anchor.BN(nonce.toString()))
+        .accountsStrict({
+          rewardToken: mint,
+          feePayer: feePayer.publicKey,
+          rewardVault,
+          rewardVaultTokenAccount: rewardVaultAta,
+          stakerRewardTokenAccount: stakerTokenAccount,
+          staker: signer.publicKey,
+          beneficiary: signer.publicKey,
+          lockup: lockup,
+          stakePda: staker1Pda,
+          stakeToken: mint,
+          stakerTokenAccount,
+          stakeVault: stakeVault,
+          stakeVaultTokenAccount: stakeVaultAta,
+          feeVault: feeVault,
+          feeVaultTokenAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          rentPayer: null,
+          receiptMint: stakerReceiptAccount ? receiptMint : null,
+          stakerReceiptAccount,
+          receiptTokenProgram: stakerReceiptAccount
+            ? TOKEN_2022_PROGRAM_ID
+            : null,
+        })
+        .signers([signer, feePayer])
+        .rpc();
+
+    // The original staker no longer holds the receipt.
+    let error: Error | undefined;
+    try {
+      await unstake(staker1, staker1Ata, null);
+    } catch (err) {
+      error = err;
+    }
+    expect(error).not.to.be.undefined;
+    expect(error.message).to.include("ReceiptRequired");
+
+    const holderAta = await getAssociatedTokenAddress(mint, holder.publicKey);
+    await unstake(holder, holderAta, holderReceiptAta);
+
+    expect(await getTokenAccountBalance(provider, holderAta)).to.be.equal(
+      BigInt(parseZbcnUnits(1000) + 6575342)
+    );
+    receipt = await getMint(
+      provider.connection,
+      receiptMint,
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    );
+    expect(receipt.supply).to.be.equal(BigInt(0));
+  });
+
+  it("redeems liquid receipts at a rate raised by funded rewards", async () => {
+    const liquidLockup = deriveLiquidLockupAddress("liquid", program.programId);
+    const liquidStakeVault = deriveStakeVaultAddress(
+      liquidLockup,
+      program.programId
+    );
+    const liquidRewardVault = deriveRewardVaultAddress(
+      liquidLockup,
+      program.programId
+    );
+    const receiptMint = deriveReceiptMintAddress(
+      liquidLockup,
+      program.programId
+    );
+    const liquidStakeVaultAta = await getAssociatedTokenAddress(
+      mint,
+      liquidStakeVault,
+      true
+    );
+    const liquidRewardVaultAta = await getAssociatedTokenAddress(
+      mint,
+      liquidRewardVault,
+      true
+    );
+    const staker1ReceiptAta = await getAssociatedTokenAddress(
+      receiptMint,
+      staker1.publicKey
+    );
+
+    await program.methods
+      .initLiquidLockup({
+        name: "liquid",
+        minimumStake: new anchor.BN(parseZbcnUnits(1)),
+      })
+      .accountsStrict({
+        creator: provider.publicKey,
+        liquidLockup,
+        stakeVault: liquidStakeVault,
+        rewardVault: liquidRewardVault,
+        stakeToken: mint,
+        receiptMint,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    const liquidStake = (amount: number) =>
+      program.methods
+        .liquidStake(new anchor.BN(amount))
+        .accountsStrict({
+          staker: staker1.publicKey,
+          feePayer: feePayer.publicKey,
+          liquidLockup,
+          stakeToken: mint,
+          stakerTokenAccount: staker1Ata,
+          stakeVault: liquidStakeVault,
+          stakeVaultTokenAccount: liquidStakeVaultAta,
+          receiptMint,
+          stakerReceiptAccount: staker1ReceiptAta,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([staker1, feePayer])
+        .rpc();
+
+    const fundLiquidRewards = (amount: number) =>
+      program.methods
+        .fundLiquidRewards(new anchor.BN(amount))
+        .accountsStrict({
+          funder: staker1.publicKey,
+          liquidLockup,
+          stakeToken: mint,
+          funderTokenAccount: staker1Ata,
+          rewardVault: liquidRewardVault,
+          rewardVaultTokenAccount: liquidRewardVaultAta,
+          receiptMint,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([staker1])
+        .rpc();
+
+    let error: Error | undefined;
+    try {
+      await fundLiquidRewards(parseZbcnUnits(250));
+    } catch (err) {
+      error = err;
+    }
+    expect(error).not.to.be.undefined;
+    expect(error.message).to.include("LiquidLockupEmpty");
+
+    // The first deposit mints one receipt per token.
+    await liquidStake(parseZbcnUnits(1000));
+    expect(
+      await getTokenAccountBalance(provider, staker1ReceiptAta)
+    ).to.be.equal(BigInt(parseZbcnUnits(1000)));
+
+    // 250 of reward lifts the rate to 1.25 tokens per receipt.
+    await fundLiquidRewards(parseZbcnUnits(250));
+    await liquidStake(parseZbcnUnits(500));
+    expect(
+      await getTokenAccountBalance(provider, staker1ReceiptAta)
+    ).to.be.equal(BigInt(parseZbcnUnits(1400)));
+
+    const pool = await program.account.liquidLockup.fetch(liquidLockup);
+    expect(pool.totalStaked.toNumber()).to.be.equal(parseZbcnUnits(1500));
+    expect(pool.totalRewards.toNumber()).to.be.equal(parseZbcnUnits(250));
+    expect(pool.roles.pauser.toBase58()).to.be.equal(
+      provider.publicKey.toBase58()
+    );
+
+    const setLiquidLockupState = (state: any) =>
+      program.methods
+        .setLiquidLockupState(state)
+        .accountsStrict({
+          pauser: provider.publicKey,
+          liquidLockup,
+        })
+        .rpc();
+
+    // Staking stops while the pool is paused.
+    await setLiquidLockupState({ paused: {} });
+    error = undefined;
+    try {
+      await liquidStake(parseZbcnUnits(500));
+    } catch (err) {
+      error = err;
+    }
+    expect(error).not.to.be.undefined;
+    expect(error.message).to.include("StakingPaused");
+    await setLiquidLockupState({ active: {} });
+
+    const balanceBefore = await getTokenAccountBalance(provider, staker1Ata);
+    await program.methods
+      .liquidUnstake(new anchor.BN(parseZbcnUnits(1400)))
+      .accountsStrict({
+        staker: staker1.publicKey,
+        feePayer: feePayer.publicKey,
+        liquidLockup,
+        stakeToken: mint,
+        stakerTokenAccount: staker1Ata,
+        stakeVault: liquidStakeVault,
+        stakeVaultTokenAccount: liquidStakeVaultAta,
+        rewardVault: liquidRewardVault,
+        rewardVaultTokenAccount: liquidRewardVaultAta,
+        receiptMint,
+        stakerReceiptAccount: staker1ReceiptAta,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([staker1, feePayer])
+      .rpc();
+
+    expect(
+      (await getTokenAccountBalance(provider, staker1Ata)) - balanceBefore
+    ).to.be.equal(BigInt(parseZbcnUnits(1750)));
+    const receipt = await getMint(provider.connection, receiptMint);
+    expect(receipt.supply).to.be.equal(BigInt(0));
+  });
+
+  it("lets a delegate manage and unstake a position for the staker", async () => {
+    const nonceInfo = await getUserNonceInfo(program, stakerNonce1);
+    const nonce = nonceInfo ? nonceInfo.nonce : BigInt(0);
+    const staker1Pda = deriveStakeAddress(
+      staker1.publicKey,
+      lockup,
+      nonce,
+      program.programId
+    );
+
+    await program.methods
+      .stakeZbcn({
+        amount: new anchor.BN(parseZbcnUnits(1000)),
+        lockPeriod: new anchor.BN(daysToSeconds(30)),
+        nonce: new anchor.BN(nonce.toString()),
+      })
+      .accountsStrict({
+        staker: staker1.publicKey,
+        feePayer: feePayer.publicKey,
+        lockup: lockup,
+        stakePda: staker1Pda,
+        userNonce: stakerNonce1,
+        stakeToken: mint,
+        stakerTokenAccount: staker1Ata,
+        stakeVault: stakeVault,
+        stakeVaultTokenAccount: stakeVaultAta,
+        rewardToken: mint,
+        rewardVault,
+        rewardVaultTokenAccount: rewardVaultAta,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        receiptMint: null,
+        stakerReceiptAccount: null,
+        receiptTokenProgram: null,
+      })
+      .signers([staker1, feePayer])
+      .rpc();
+
+    const custodian = Keypair.generate();
+    await program.methods
+      .setDelegate(new anchor.BN(nonce.toString()), custodian.publicKey)
+      .accountsStrict({
+        staker: staker1.publicKey,
+        lockup: lockup,
+        stakePda: staker1Pda,
+      })
+      .signers([staker1])
+      .rpc();
+
+    let stakeAccount = await program.account.userStakeData.fetch(staker1Pda);
+    expect(stakeAccount.delegate.toBase58()).to.be.equal(
+      custodian.publicKey.toBase58()
+    );
+
+    const setAutoRenew = (autoRenew: boolean) =>
+      program.methods
+        .setAutoRenew({
+          nonce: new anchor.BN(nonce.toString()),
+          autoRenew,
+          compoundOnRenew: false,
+        })
+        .accountsStrict({
+          staker: custodian.publicKey,
+          lockup: lockup,
+          stakePda: staker1Pda,
+        })
+        .signers([custodian])
+        .rpc();
+
+    await setAutoRenew(true);
+    stakeAccount = await program.account.userStakeData.fetch(staker1Pda);
+    expect(stakeAccount.autoRenew).to.be.true;
+
+    await warpForward(context, daysToSeconds(31));
+
+    // The delegate signs, but the payout lands in the staker's accounts.
+    const balanceBefore = await getTokenAccountBalance(provider, staker1Ata);
+    const unstake = (beneficiary: PublicKey, beneficiaryAta: PublicKey) =>
+      program.methods
+        .unstakeZbcn(new anchor.BN(nonce.toString()))
+        .accountsStrict({
+          rewardToken: mint,
+          feePayer: feePayer.publicKey,
+          rewardVault,
+          rewardVaultTokenAccount: rewardVaultAta,
+          stakerRewardTokenAccount: beneficiaryAta,
+          staker: custodian.publicKey,
+          beneficiary,
+          lockup: lockup,
+          stakePda: staker1Pda,
+          stakeToken: mint,
+          stakerTokenAccount: beneficiaryAta,
+          stakeVault: stakeVault,
+          stakeVaultTokenAccount: stakeVaultAta,
+          feeVault: feeVault,
+          feeVaultTokenAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          rentPayer: null,
+          receiptMint: null,
+          stakerReceiptAccount: null,
+          receiptTokenProgram: null,
+        })
+        .signers([custodian, feePayer])
+        .rpc();
+
+    let unstakeError: Error | undefined;
+    try {
+      await unstake(
+        custodian.publicKey,
+        await getAssociatedTokenAddress(mint, custodian.publicKey)
+      );
+    } catch (err) {
+      unstakeError = err;
+    }
+    expect(unstakeError).not.to.be.undefined;
+    expect(unstakeError.message).to.include("InvalidStaker");
+
+    await unstake(staker1.publicKey, staker1Ata);
+    expect(
+      (await getTokenAccountBalance(provider, staker1Ata)) - balanceBefore
+    ).to.be.equal(BigInt(parseZbcnUnits(1000) + 6575342));
+
+    await program.methods
+      .revokeDelegate(new anchor.BN(nonce.toString()))
+      .accountsStrict({
+        staker: staker1.publicKey,
+        lockup: lockup,
+        stakePda: staker1Pda,
+      })
+      .signers([staker1])
+      .rpc();
+
+    let error: Error | undefined;
+    try {
+      await setAutoRenew(false);
+    } catch (err) {
+      error = err;
+    }
+    expect(error).not.to.be.undefined;
+    expect(error.message).to.include("UnAuthorized");
+  });
+
+  it("lets a delegate take a position through the cooldown", async () => {
+    const setCooldown = (cooldownSeconds: number) =>
+      program.methods
+        .setLockupOptions({
+          topUpPolicy: { resetLock: {} },
+          claimEnabled: false,
+          cooldownSeconds: new anchor.BN(cooldownSeconds),
+          transfersEnabled: false,
+        })
+        .accountsStrict({
+          updater: provider.publicKey,
+          lockup: lockup,
+        })
+        .rpc();
+
+    await setCooldown(daysToSeconds(1));
+
+    const nonceInfo = await getUserNonceInfo(program, stakerNonce1);
+    const nonce = nonceInfo ? nonceInfo.nonce : BigInt(0);
+    const staker1Pda = deriveStakeAddress(
+      staker1.publicKey,
+      lockup,
+      nonce,
+      program.programId
+    );
+
+    await program.methods
+      .stakeZbcn({
+        amount: new anchor.BN(parseZbcnUnits(1000)),
+        lockPeriod: new anchor.BN(daysToSeconds(30)),
+        nonce: new anchor.BN(nonce.toString()),
+      })
+      .accountsStrict({
+        staker: staker1.publicKey,
+        feePayer: feePayer.publicKey,
+        lockup: lockup,
+        stakePda: staker1Pda,
+        userNonce: stakerNonce1,
+        stakeToken: mint,
+        stakerTokenAccount: staker1Ata,
+        stakeVault: stakeVault,
+        stakeVaultTokenAccount: stakeVaultAta,
+        rewardToken: mint,
+        rewardVault,
+        rewardVaultTokenAccount: rewardVaultAta,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        receiptMint: null,
+        stakerReceiptAccount: null,
+        receiptTokenProgram: null,
+      })
+      .signers([staker1, feePayer])
+      .rpc();
+
+    const custodian = Keypair.generate();
+    await program.methods
+      .setDelegate(new anchor.BN(nonce.toString()), custodian.publicKey)
+      .accountsStrict({
+        staker: staker1.publicKey,
+        lockup: lockup,
+        stakePda: staker1Pda,
+      })
+      .signers([staker1])
+      .rpc();
+
+    await warpForward(context, daysToSeconds(31));
+
+    const requestUnstake = (beneficiary: PublicKey, beneficiaryAta: PublicKey) =>
+      program.methods
+        .requestUnstake(new anchor.BN(nonce.toString()))
+        .accountsStrict({
+          staker: custodian.publicKey,
+          beneficiary,
+          feePayer: feePayer.publicKey,
+          lockup: lockup,
+          stakePda: staker1Pda,
+          rewardToken: mint,
+          stakerRewardTokenAccount: beneficiaryAta,
+          rewardVault,
+          rewardVaultTokenAccount: rewardVaultAta,
+          receiptMint: null,
+          stakerReceiptAccount: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([custodian, feePayer])
+        .rpc();
+
+    // The delegate cannot route the payout to itself.
+    let error: Error | undefined;
+    try {
+      await requestUnstake(
+        custodian.publicKey,
+        await getAssociatedTokenAddress(mint, custodian.publicKey)
+      );
+    } catch (err) {
+      error = err;
+    }
+    expect(error).not.to.be.undefined;
+    expect(error.message).to.include("InvalidStaker");
+
+    let balanceBefore = await getTokenAccountBalance(provider, staker1Ata);
+    await requestUnstake(staker1.publicKey, staker1Ata);
+    expect(
+      (await getTokenAccountBalance(provider, staker1Ata)) - balanceBefore
+    ).to.be.equal(BigInt(6575342));
+
+    await warpForward(context, daysToSeconds(1));
+
+    balanceBefore = await getTokenAccountBalance(provider, staker1Ata);
+    await program.methods
+      .withdraw(new anchor.BN(nonce.toString()))
+      .accountsStrict({
+        staker: custodian.publicKey,
+        beneficiary: staker1.publicKey,
+        feePayer: feePayer.publicKey,
+        lockup: lockup,
+        stakePda: staker1Pda,
+        stakeToken: mint,
+        stakerTokenAccount: staker1Ata,
+        stakeVault: stakeVault,
+        stakeVaultTokenAccount: stakeVaultAta,
+        feeVaultTokenAccount,
+        rentPayer: null,
+        receiptMint: null,
+        stakerReceiptAccount: null,
+        receiptTokenProgram: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([custodian, feePayer])
+      .rpc();
+    expect(
+      (await getTokenAccountBalance(provider, staker1Ata)) - balanceBefore
+    ).to.be.equal(BigInt(parseZbcnUnits(1000)));
+
+    const stakeAccount = await program.account.userStakeData.fetch(staker1Pda);
+    expect(stakeAccount.stakeClaimed).to.be.true;
+
+    await setCooldown(0);
+  });
+
+  it("lets the receipt holder withdraw a tokenized position after the cooldown", async () => {
+    const setCooldown = (cooldownSeconds: number) =>
+      program.methods
+        .setLockupOptions({
+          topUpPolicy: { resetLock: {} },
+          claimEnabled: false,
+          cooldownSeconds: new anchor.BN(cooldownSeconds),
+          transfersEnabled: false,
+        })
+        .accountsStrict({
+          updater: provider.publicKey,
+          lockup: lockup,
+        })
+        .rpc();
+
+    await setCooldown(daysToSeconds(1));
+
+    const nonceInfo = await getUserNonceInfo(program, stakerNonce1);
+    const nonce = nonceInfo ? nonceInfo.nonce : BigInt(0);
+    const staker1Pda = deriveStakeAddress(
+      staker1.publicKey,
+      lockup,
+      nonce,
+      program.programId
+    );
+    const receiptMint = deriveReceiptMintAddress(staker1Pda, program.programId);
+    const staker1ReceiptAta = await getAssociatedTokenAddress(
+      receiptMint,
+      staker1.publicKey,
+      false,
+      TOKEN_2022_PROGRAM_ID
+    );
+
+    await program.methods
+      .stakeZbcn({
+        amount: new anchor.BN(parseZbcnUnits(1000)),
+        lockPeriod: new anchor.BN(daysToSeconds(30)),
+        nonce: new anchor.BN(nonce.toString()),
+      })
+      .accountsStrict({
+        staker: staker1.publicKey,
+        feePayer: feePayer.publicKey,
+        lockup: lockup,
+        stakePda: staker1Pda,
+        userNonce: stakerNonce1,
+        stakeToken: mint,
+        stakerTokenAccount: staker1Ata,
+        stakeVault: stakeVault,
+        stakeVaultTokenAccount: stakeVaultAta,
+        rewardToken: mint,
+        rewardVault,
+        rewardVaultTokenAccount: rewardVaultAta,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        receiptMint,
+        stakerReceiptAccount: staker1ReceiptAta,
+        receiptTokenProgram: TOKEN_2022_PROGRAM_ID,
+      })
+      .signers([staker1, feePayer])
+      .rpc();
+
+    const holder = Keypair.generate();
+    const holderReceiptAta = await transferReceipt(
+      provider,
+      receiptMint,
+      staker1ReceiptAta,
+      holder.publicKey
+    );
+    const holderAta = await getAssociatedTokenAddress(mint, holder.publicKey);
+
+    await warpForward(context, daysToSeconds(31));
+
+    await program.methods
+      .requestUnstake(new anchor.BN(nonce.toString()))
+      .accountsStrict({
+        staker: holder.publicKey,
+        beneficiary: holder.publicKey,
+        feePayer: feePayer.publicKey,
+        lockup: lockup,
+        stakePda: staker1Pda,
+        rewardToken: mint,
+        stakerRewardTokenAccount: holderAta,
+        rewardVault,
+        rewardVaultTokenAccount: rewardVaultAta,
+        receiptMint,
+        stakerReceiptAccount: holderReceiptAta,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([holder, feePayer])
+      .rpc();
+    expect(await getTokenAccountBalance(provider, holderAta)).to.be.equal(
+      BigInt(6575342)
+    );
+
+    await warpForward(context, daysToSeconds(1));
+
+    await program.methods
+      .withdraw(new anchor.BN(nonce.toString()))
+      .accountsStrict({
+        staker: holder.publicKey,
+        beneficiary: holder.publicKey,
+        feePayer: feePayer.publicKey,
+        lockup: lockup,
+        stakePda: staker1Pda,
+        stakeToken: mint,
+        stakerTokenAccount: holderAta,
+        stakeVault: stakeVault,
+        stakeVaultTokenAccount: stakeVaultAta,
+        feeVaultTokenAccount,
+        rentPayer: null,
+        receiptMint,
+        stakerReceiptAccount: holderReceiptAta,
+        receiptTokenProgram: TOKEN_2022_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([holder, feePayer])
+      .rpc();
+
+    expect(await getTokenAccountBalance(provider, holderAta)).to.be.equal(
+      BigInt(parseZbcnUnits(1000) + 6575342)
+    );
+    const receipt = await getMint(
+      provider.connection,
+      receiptMint,
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    );
+    expect(receipt.supply).to.be.equal(BigInt(0));
+
+    await setCooldown(0);
+  });
+
+  it("rejects a fee above the fee denominator", async () => {
+    let error: Error | undefined;
+    try {
+      await program.methods
+        .updateLockup({
+          fee: new anchor.BN(1001),
+          feeVault: lockUpData.feeVault,
+          minimumStake: lockUpData.minimumStake,
+          durationMap: rewardSchemes,
+        })
+        .accountsStrict({
+          updater: provider.publicKey,
+          lockup: lockup,
+        })
+        .rpc();
+    } catch (err) {
+      error = err;
+    }
+    expect(error).not.to.be.undefined;
+    expect(error.message).to.include("InvalidFee");
   });
 });
@@ -15,6 +15,7 @@ import {
   getAssociatedTokenAddress,
   getMinimumBalanceForRentExemptMint,
   MINT_SIZE,
+  TOKEN_2022_PROGRAM_ID,
   TOKEN_PROGRAM_ID,
 } from '@solana/spl-token';
 import {
@@ -23,6 +24,7 @@ import {
   SystemProgram,
   Transaction,
 } from '@solana/web3.js';
+import { Clock, ProgramTestContext } from 'solana-bankrun';
 
 import { ZebecStake } from '../target/types/zebec_stake';
 
@@ -110,6 +112,42 @@ export async function fundTokenAccount(
   await provider.sendAndConfirm(transaction);
 }
 
+// Moves a position receipt to `to`, creating its Token-2022 account.
+export async function transferReceipt(
+  provider: Provider,
+  receiptMint: PublicKey,
+  fromAta: PublicKey,
+  to: PublicKey
+) {
+  const toAta = await getAssociatedTokenAddress(
+    receiptMint,
+    to,
+    false,
+    TOKEN_2022_PROGRAM_ID
+  );
+  const transaction = new Transaction().add(
+    createAssociatedTokenAccountInstruction(
+      provider.publicKey,
+      toAta,
+      to,
+      receiptMint,
+      TOKEN_2022_PROGRAM_ID
+    ),
+    createTransferCheckedInstruction(
+      fromAta,
+      receiptMint,
+      toAta,
+      provider.publicKey,
+      1,
+      0,
+      [],
+      TOKEN_2022_PROGRAM_ID
+    )
+  );
+  await provider.sendAndConfirm(transaction);
+  return toAta;
+}
+
 export async function getFeeVault(provider: Provider, mint: PublicKey) {
   const feeVault = Keypair.generate();
   const feeVaultTokenAccount = await getAssociatedTokenAddress(
@@ -138,6 +176,92 @@ export async function getTokenAccountBalance(
   return balance.amount;
 }
 
+export async function warpForward(context: ProgramTestContext, seconds: number) {
+  const currentClock = await context.banksClient.getClock();
+  context.setClock(
+    new Clock(
+      currentClock.slot,
+      currentClock.epochStartTimestamp,
+      currentClock.epoch,
+      currentClock.leaderScheduleEpoch,
+      currentClock.unixTimestamp + BigInt(seconds)
+    )
+  );
+}
+
+export type LegacyStakeData = {
+  nonce: bigint;
+  createdTime: bigint;
+  stakedAmount: bigint;
+  rewardAmount: bigint;
+  stakeClaimed: boolean;
+  lockPeriod: bigint;
+  staker: PublicKey;
+  lockup: PublicKey;
+};
+
+/** Encodes a `UserStakeData` account as written before `migrate_stake` existed. */
+export function encodeLegacyStakeData(
+  program: Program<ZebecStake>,
+  stake: LegacyStakeData
+): Buffer {
+  const data = Buffer.alloc(113);
+  Buffer.from(
+    program.idl.accounts.find((account) => account.name === "userStakeData")
+      .discriminator
+  ).copy(data, 0);
+  data.writeBigUInt64LE(stake.nonce, 8);
+  data.writeBigInt64LE(stake.createdTime, 16);
+  data.writeBigUInt64LE(stake.stakedAmount, 24);
+  data.writeBigUInt64LE(stake.rewardAmount, 32);
+  data.writeUInt8(stake.stakeClaimed ? 1 : 0, 40);
+  data.writeBigInt64LE(stake.lockPeriod, 41);
+  stake.staker.toBuffer().copy(data, 49);
+  stake.lockup.toBuffer().copy(data, 81);
+  return data;
+}
+
+export type LegacyLockupData = {
+  name: string;
+  creator: PublicKey;
+  minimumStake: bigint;
+  durationMap: { duration: bigint; reward: bigint }[];
+  rewardToken: PublicKey;
+  stakedToken: PublicKey;
+  totalStaked: bigint;
+  fee: bigint;
+  feeVault: PublicKey;
+};
+
+/** Encodes a `Lockup` account as written before `migrate_lockup` existed. */
+export function encodeLegacyLockupData(
+  program: Program<ZebecStake>,
+  lockup: LegacyLockupData
+): Buffer {
+  const data = Buffer.alloc(4194);
+  Buffer.from(
+    program.idl.accounts.find((account) => account.name === "lockup")
+      .discriminator
+  ).copy(data, 0);
+  let offset = 8;
+  const name = Buffer.from(lockup.name);
+  offset = data.writeUInt32LE(name.length, offset);
+  offset += name.copy(data, offset);
+  offset += lockup.creator.toBuffer().copy(data, offset);
+  offset = data.writeBigUInt64LE(lockup.minimumStake, offset);
+  offset = data.writeUInt32LE(lockup.durationMap.length, offset);
+  for (const { duration, reward } of lockup.durationMap) {
+    offset = data.writeBigUInt64LE(duration, offset);
+    offset = data.writeBigUInt64LE(reward, offset);
+  }
+  offset += lockup.rewardToken.toBuffer().copy(data, offset);
+  offset += lockup.stakedToken.toBuffer().copy(data, offset);
+  offset = data.writeBigUInt64LE(lockup.totalStaked, offset);
+  offset = data.writeBigUInt64LE(lockup.fee, offset);
+  lockup.feeVault.toBuffer().copy(data, offset);
+  return data;
+}
+
 export function daysToSeconds(days: number) {
   return days * 24 * 60 * 60;
 }